@@ -0,0 +1,400 @@
+//! Generic compensated accumulation.
+
+use std::fmt;
+
+use crate::summation::{Neumaier, Summation};
+use crate::{AccumulatorN, Vec3, Vec3Accumulator, Vec3AccumulatorF32, Vec3F32, VecN};
+
+/// A value type that can be accumulated with compensated summation
+///
+/// Implementors name the compensated state used to accumulate them and
+/// describe how values are folded into it. [`Accumulator<T>`] wraps that
+/// state behind a uniform API, so code can be written once for scalars,
+/// vectors and downstream types alike.
+///
+/// # Implementing for your own types
+///
+/// Compose the state from existing accumulators, one per component:
+///
+/// ```rust
+/// use drift_linalg::{Accumulator, CompensatedAccumulate};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct Energy {
+///     kinetic: f64,
+///     potential: f64,
+/// }
+///
+/// impl CompensatedAccumulate for Energy {
+///     type State = (Accumulator<f64>, Accumulator<f64>);
+///
+///     fn new_state() -> Self::State {
+///         (Accumulator::new(), Accumulator::new())
+///     }
+///     fn add(state: &mut Self::State, value: Self) {
+///         state.0.add(value.kinetic);
+///         state.1.add(value.potential);
+///     }
+///     fn add_scaled(state: &mut Self::State, value: Self, scalar: f64) {
+///         state.0.add_scaled(value.kinetic, scalar);
+///         state.1.add_scaled(value.potential, scalar);
+///     }
+///     fn resolve(state: &Self::State) -> Self {
+///         Energy { kinetic: state.0.resolve(), potential: state.1.resolve() }
+///     }
+///     fn reset(state: &mut Self::State) {
+///         state.0.reset();
+///         state.1.reset();
+///     }
+///     fn merge(state: &mut Self::State, other: &Self::State) {
+///         state.0.merge(&other.0);
+///         state.1.merge(&other.1);
+///     }
+/// }
+///
+/// let mut total = Accumulator::<Energy>::new();
+/// total.add(Energy { kinetic: 1e16, potential: 1.0 });
+/// total.add(Energy { kinetic: 1.0, potential: 0.0 });
+/// total.add(Energy { kinetic: -1e16, potential: 0.0 });
+/// assert_eq!(total.resolve(), Energy { kinetic: 1.0, potential: 1.0 });
+/// ```
+pub trait CompensatedAccumulate: Sized {
+    /// The compensated running state.
+    type State: Clone + fmt::Debug;
+
+    /// Create a zero-initialized state.
+    fn new_state() -> Self::State;
+
+    /// Add a value to the state.
+    fn add(state: &mut Self::State, value: Self);
+
+    /// Add a scaled value to the state.
+    fn add_scaled(state: &mut Self::State, value: Self, scalar: f64);
+
+    /// Extract the compensated total.
+    fn resolve(state: &Self::State) -> Self;
+
+    /// Reset the state to zero.
+    fn reset(state: &mut Self::State);
+
+    /// Fold another state, including its compensation, into this one.
+    fn merge(state: &mut Self::State, other: &Self::State);
+}
+
+impl CompensatedAccumulate for f64 {
+    type State = Neumaier;
+
+    #[inline]
+    fn new_state() -> Neumaier {
+        Neumaier::new(0.0)
+    }
+
+    #[inline]
+    fn add(state: &mut Neumaier, value: f64) {
+        state.add(value);
+    }
+
+    #[inline]
+    fn add_scaled(state: &mut Neumaier, value: f64, scalar: f64) {
+        state.add(value * scalar);
+    }
+
+    #[inline]
+    fn resolve(state: &Neumaier) -> f64 {
+        state.total()
+    }
+
+    #[inline]
+    fn reset(state: &mut Neumaier) {
+        state.reset();
+    }
+
+    #[inline]
+    fn merge(state: &mut Neumaier, other: &Neumaier) {
+        state.merge(other);
+    }
+}
+
+impl CompensatedAccumulate for Vec3 {
+    type State = Vec3Accumulator;
+
+    #[inline]
+    fn new_state() -> Vec3Accumulator {
+        Vec3Accumulator::new()
+    }
+
+    #[inline]
+    fn add(state: &mut Vec3Accumulator, value: Vec3) {
+        state.add(value);
+    }
+
+    #[inline]
+    fn add_scaled(state: &mut Vec3Accumulator, value: Vec3, scalar: f64) {
+        state.add_scaled(value, scalar);
+    }
+
+    #[inline]
+    fn resolve(state: &Vec3Accumulator) -> Vec3 {
+        state.resolve()
+    }
+
+    #[inline]
+    fn reset(state: &mut Vec3Accumulator) {
+        state.reset();
+    }
+
+    #[inline]
+    fn merge(state: &mut Vec3Accumulator, other: &Vec3Accumulator) {
+        state.merge(other);
+    }
+}
+
+impl CompensatedAccumulate for f32 {
+    type State = Neumaier<f32>;
+
+    #[inline]
+    fn new_state() -> Neumaier<f32> {
+        Neumaier::new(0.0)
+    }
+
+    #[inline]
+    fn add(state: &mut Neumaier<f32>, value: f32) {
+        state.add(value);
+    }
+
+    /// The scalar is rounded to f32 before the multiplication.
+    #[inline]
+    fn add_scaled(state: &mut Neumaier<f32>, value: f32, scalar: f64) {
+        state.add(value * scalar as f32);
+    }
+
+    #[inline]
+    fn resolve(state: &Neumaier<f32>) -> f32 {
+        state.total()
+    }
+
+    #[inline]
+    fn reset(state: &mut Neumaier<f32>) {
+        state.reset();
+    }
+
+    #[inline]
+    fn merge(state: &mut Neumaier<f32>, other: &Neumaier<f32>) {
+        state.merge(other);
+    }
+}
+
+impl CompensatedAccumulate for Vec3F32 {
+    type State = Vec3AccumulatorF32;
+
+    #[inline]
+    fn new_state() -> Vec3AccumulatorF32 {
+        Vec3AccumulatorF32::default()
+    }
+
+    #[inline]
+    fn add(state: &mut Vec3AccumulatorF32, value: Vec3F32) {
+        state.add(value);
+    }
+
+    /// The scalar is rounded to f32 before the multiplication.
+    #[inline]
+    fn add_scaled(state: &mut Vec3AccumulatorF32, value: Vec3F32, scalar: f64) {
+        state.add_scaled(value, scalar as f32);
+    }
+
+    #[inline]
+    fn resolve(state: &Vec3AccumulatorF32) -> Vec3F32 {
+        state.resolve()
+    }
+
+    #[inline]
+    fn reset(state: &mut Vec3AccumulatorF32) {
+        state.reset();
+    }
+
+    #[inline]
+    fn merge(state: &mut Vec3AccumulatorF32, other: &Vec3AccumulatorF32) {
+        state.merge(other);
+    }
+}
+
+impl<const N: usize> CompensatedAccumulate for VecN<N> {
+    type State = AccumulatorN<N>;
+
+    #[inline]
+    fn new_state() -> AccumulatorN<N> {
+        AccumulatorN::new()
+    }
+
+    #[inline]
+    fn add(state: &mut AccumulatorN<N>, value: VecN<N>) {
+        state.add(value);
+    }
+
+    #[inline]
+    fn add_scaled(state: &mut AccumulatorN<N>, value: VecN<N>, scalar: f64) {
+        state.add_scaled(value, scalar);
+    }
+
+    #[inline]
+    fn resolve(state: &AccumulatorN<N>) -> VecN<N> {
+        state.resolve()
+    }
+
+    #[inline]
+    fn reset(state: &mut AccumulatorN<N>) {
+        state.reset();
+    }
+
+    #[inline]
+    fn merge(state: &mut AccumulatorN<N>, other: &AccumulatorN<N>) {
+        state.merge(other);
+    }
+}
+
+/// A compensated accumulator for any [`CompensatedAccumulate`] type
+///
+/// `Accumulator<Vec3>` behaves exactly like [`Vec3Accumulator`], and
+/// `Accumulator<f64>` is a scalar Neumaier sum. The f32 counterparts,
+/// `Accumulator<f32>` and `Accumulator<Vec3F32>`, are also provided.
+///
+/// # Example
+///
+/// ```rust
+/// use drift_linalg::{Accumulator, Vec3};
+///
+/// fn integrate<T: drift_linalg::CompensatedAccumulate + Copy>(
+///     acc: &mut Accumulator<T>,
+///     rate: T,
+///     dt: f64,
+///     steps: usize,
+/// ) {
+///     for _ in 0..steps {
+///         acc.add_scaled(rate, dt);
+///     }
+/// }
+///
+/// let mut energy = Accumulator::<f64>::new();
+/// let mut position = Accumulator::<Vec3>::new();
+/// integrate(&mut energy, 2.0, 0.5, 10);
+/// integrate(&mut position, Vec3::new(2.0, 0.0, 0.0), 0.5, 10);
+/// assert_eq!(energy.resolve(), 10.0);
+/// assert_eq!(position.resolve().x, 10.0);
+/// ```
+pub struct Accumulator<T: CompensatedAccumulate> {
+    state: T::State,
+}
+
+impl<T: CompensatedAccumulate> Accumulator<T> {
+    /// Create a new zero-initialized accumulator.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            state: T::new_state(),
+        }
+    }
+
+    /// Create an accumulator with an initial value.
+    #[inline]
+    pub fn with_initial(initial: T) -> Self {
+        let mut acc = Self::new();
+        acc.add(initial);
+        acc
+    }
+
+    /// Add a value to the accumulator.
+    #[inline]
+    pub fn add(&mut self, value: T) {
+        T::add(&mut self.state, value);
+    }
+
+    /// Add a scaled value to the accumulator.
+    #[inline]
+    pub fn add_scaled(&mut self, value: T, scalar: f64) {
+        T::add_scaled(&mut self.state, value, scalar);
+    }
+
+    /// Resolve the accumulator to its compensated total.
+    #[inline]
+    pub fn resolve(&self) -> T {
+        T::resolve(&self.state)
+    }
+
+    /// Reset the accumulator to zero.
+    #[inline]
+    pub fn reset(&mut self) {
+        T::reset(&mut self.state);
+    }
+
+    /// Fold another accumulator into this one without resolving either.
+    #[inline]
+    pub fn merge(&mut self, other: &Self) {
+        T::merge(&mut self.state, &other.state);
+    }
+
+    /// Borrow the underlying compensated state.
+    #[inline]
+    pub fn state(&self) -> &T::State {
+        &self.state
+    }
+}
+
+impl<T: CompensatedAccumulate> Default for Accumulator<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: CompensatedAccumulate> Clone for Accumulator<T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T: CompensatedAccumulate> fmt::Debug for Accumulator<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Accumulator")
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accumulator_f64_cancellation() {
+        let mut acc = Accumulator::<f64>::new();
+        acc.add(1e16);
+        acc.add(1.0);
+        acc.add(-1e16);
+        assert_eq!(acc.resolve(), 1.0);
+    }
+
+    #[test]
+    fn accumulator_vec3_matches_vec3_accumulator() {
+        let mut generic = Accumulator::<Vec3>::with_initial(Vec3::new(1e15, 0.0, -1.0));
+        let mut concrete = Vec3Accumulator::with_initial(Vec3::new(1e15, 0.0, -1.0));
+        for i in 0..1000 {
+            let v = Vec3::new(0.1 * i as f64, -1e-3, 7.0);
+            generic.add_scaled(v, 1.0 / 60.0);
+            concrete.add_scaled(v, 1.0 / 60.0);
+        }
+        assert_eq!(generic.state().to_le_bytes(), concrete.to_le_bytes());
+    }
+
+    #[test]
+    fn accumulator_merge_and_reset() {
+        let mut a = Accumulator::<f64>::with_initial(1e16);
+        let mut b = Accumulator::<f64>::with_initial(1.0);
+        b.add(-1e16);
+        a.merge(&b);
+        assert_eq!(a.resolve(), 1.0);
+        a.reset();
+        assert_eq!(a.resolve(), 0.0);
+    }
+}
@@ -0,0 +1,314 @@
+//! Double-double arithmetic for high-precision coordinates.
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+use crate::summation::Neumaier;
+use crate::{eft, Vec3, Vec3Accumulator};
+
+/// An unevaluated sum `hi + lo` of two f64 values
+///
+/// Carries roughly 106 bits of significand. Values produced by this crate
+/// are normalized: `hi` is `hi + lo` rounded to nearest, so `hi` alone is
+/// always the best f64 approximation.
+///
+/// The operations follow Joldes, Muller and Popescu, "Tight and rigorous
+/// error bounds for basic building blocks of double-word arithmetic" (2017),
+/// and use only `+ - *` and FMA, so results are bit-reproducible.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct DoubleDouble {
+    pub hi: f64,
+    pub lo: f64,
+}
+
+impl DoubleDouble {
+    /// Zero.
+    pub const ZERO: Self = Self { hi: 0.0, lo: 0.0 };
+
+    /// Create a DoubleDouble from parts that are already normalized.
+    #[inline]
+    pub const fn new(hi: f64, lo: f64) -> Self {
+        Self { hi, lo }
+    }
+
+    /// The exact sum of two f64 values.
+    #[inline]
+    pub fn from_sum(a: f64, b: f64) -> Self {
+        let (hi, lo) = eft::two_sum(a, b);
+        Self { hi, lo }
+    }
+
+    /// The exact product of two f64 values.
+    #[inline]
+    pub fn from_product(a: f64, b: f64) -> Self {
+        let (hi, lo) = eft::two_product(a, b);
+        Self { hi, lo }
+    }
+
+    /// Round to the nearest f64.
+    #[inline]
+    pub fn to_f64(self) -> f64 {
+        self.hi + self.lo
+    }
+
+    /// Multiply by an f64 (relative error below 2u²).
+    #[inline]
+    pub fn scale(self, scalar: f64) -> Self {
+        let (ch, cl1) = eft::two_product(self.hi, scalar);
+        let cl2 = self.lo * scalar;
+        let (th, tl1) = eft::fast_two_sum(ch, cl2);
+        let (hi, lo) = eft::fast_two_sum(th, tl1 + cl1);
+        Self { hi, lo }
+    }
+}
+
+impl From<f64> for DoubleDouble {
+    fn from(value: f64) -> Self {
+        Self { hi: value, lo: 0.0 }
+    }
+}
+
+impl std::ops::Add for DoubleDouble {
+    type Output = Self;
+    /// Accurate double-double addition (relative error below 3u²).
+    fn add(self, rhs: Self) -> Self {
+        let (sh, sl) = eft::two_sum(self.hi, rhs.hi);
+        let (th, tl) = eft::two_sum(self.lo, rhs.lo);
+        let (vh, vl) = eft::fast_two_sum(sh, sl + th);
+        let (hi, lo) = eft::fast_two_sum(vh, tl + vl);
+        Self { hi, lo }
+    }
+}
+
+impl std::ops::Sub for DoubleDouble {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + (-rhs)
+    }
+}
+
+impl std::ops::Mul for DoubleDouble {
+    type Output = Self;
+    /// Double-double multiplication (relative error below 4u²).
+    fn mul(self, rhs: Self) -> Self {
+        let (ch, cl1) = eft::two_product(self.hi, rhs.hi);
+        let tl0 = self.lo * rhs.lo;
+        let tl1 = self.hi.mul_add(rhs.lo, tl0);
+        let cl2 = self.lo.mul_add(rhs.hi, tl1);
+        let (hi, lo) = eft::fast_two_sum(ch, cl1 + cl2);
+        Self { hi, lo }
+    }
+}
+
+impl std::ops::Neg for DoubleDouble {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self {
+            hi: -self.hi,
+            lo: -self.lo,
+        }
+    }
+}
+
+/// A 3D vector with double-double components
+///
+/// For positions that need more than 53 bits of precision, such as
+/// planetary-scale world coordinates. Converting a [`Vec3Accumulator`] keeps
+/// its compensation term as the `lo` part instead of rounding it away.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct Vec3DD {
+    pub x: DoubleDouble,
+    pub y: DoubleDouble,
+    pub z: DoubleDouble,
+}
+
+impl Vec3DD {
+    /// The zero vector.
+    pub const ZERO: Self = Self {
+        x: DoubleDouble::ZERO,
+        y: DoubleDouble::ZERO,
+        z: DoubleDouble::ZERO,
+    };
+
+    /// Create a new Vec3DD.
+    #[inline]
+    pub const fn new(x: DoubleDouble, y: DoubleDouble, z: DoubleDouble) -> Self {
+        Self { x, y, z }
+    }
+
+    /// The high parts, i.e. each component rounded to the nearest f64.
+    #[inline]
+    pub fn hi(&self) -> Vec3 {
+        Vec3::new(self.x.hi, self.y.hi, self.z.hi)
+    }
+
+    /// The low parts.
+    #[inline]
+    pub fn lo(&self) -> Vec3 {
+        Vec3::new(self.x.lo, self.y.lo, self.z.lo)
+    }
+
+    /// Round each component to the nearest f64.
+    #[inline]
+    pub fn to_vec3(&self) -> Vec3 {
+        Vec3::new(self.x.to_f64(), self.y.to_f64(), self.z.to_f64())
+    }
+
+    /// Returns the raw IEEE-754 little-endian bytes.
+    ///
+    /// Layout is `x.hi, x.lo, y.hi, y.lo, z.hi, z.lo` (48 bytes).
+    #[inline]
+    pub fn to_le_bytes(&self) -> [u8; 48] {
+        let mut buf = [0u8; 48];
+        for (i, v) in [self.x, self.y, self.z].iter().enumerate() {
+            buf[i * 16..i * 16 + 8].copy_from_slice(&v.hi.to_le_bytes());
+            buf[i * 16 + 8..i * 16 + 16].copy_from_slice(&v.lo.to_le_bytes());
+        }
+        buf
+    }
+
+    /// Reconstruct a Vec3DD from little-endian bytes.
+    ///
+    /// This is the inverse of [`to_le_bytes`](Self::to_le_bytes).
+    #[inline]
+    pub fn from_le_bytes(bytes: [u8; 48]) -> Self {
+        let component = |i: usize| DoubleDouble {
+            hi: f64::from_le_bytes(bytes[i * 16..i * 16 + 8].try_into().unwrap()),
+            lo: f64::from_le_bytes(bytes[i * 16 + 8..i * 16 + 16].try_into().unwrap()),
+        };
+        Self {
+            x: component(0),
+            y: component(1),
+            z: component(2),
+        }
+    }
+
+    /// Compute the dot product in double-double precision.
+    #[inline]
+    pub fn dot(&self, other: Vec3DD) -> DoubleDouble {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Scale by a scalar.
+    #[inline]
+    pub fn scale(&self, scalar: f64) -> Self {
+        Self {
+            x: self.x.scale(scalar),
+            y: self.y.scale(scalar),
+            z: self.z.scale(scalar),
+        }
+    }
+}
+
+impl From<Vec3> for Vec3DD {
+    fn from(v: Vec3) -> Self {
+        Self {
+            x: v.x.into(),
+            y: v.y.into(),
+            z: v.z.into(),
+        }
+    }
+}
+
+impl From<Vec3DD> for Vec3 {
+    fn from(v: Vec3DD) -> Self {
+        v.to_vec3()
+    }
+}
+
+impl From<&Vec3Accumulator> for Vec3DD {
+    /// Keep each axis's running sum and compensation as a normalized
+    /// double-double, instead of rounding them together as
+    /// [`resolve`](Vec3Accumulator::resolve) does.
+    fn from(acc: &Vec3Accumulator) -> Self {
+        let (x, y, z) = acc.components();
+        let dd = |n: &Neumaier| DoubleDouble::from_sum(n.sum(), n.compensation());
+        Self {
+            x: dd(x),
+            y: dd(y),
+            z: dd(z),
+        }
+    }
+}
+
+impl std::ops::Add for Vec3DD {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+
+impl std::ops::Sub for Vec3DD {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+impl std::ops::Neg for Vec3DD {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vec3dd_keeps_sub_ulp_offsets() {
+        // 1e17 has an ulp of 16; a millimeter offset survives only in `lo`.
+        let far = Vec3DD::from(Vec3::new(1e17, -1e17, 4e17));
+        let offset = Vec3DD::from(Vec3::new(1e-3, 2e-3, 3e-3));
+        let moved = far + offset;
+        assert_eq!(moved.hi(), far.hi());
+        assert_eq!((moved - far).to_vec3(), Vec3::new(1e-3, 2e-3, 3e-3));
+    }
+
+    #[test]
+    fn vec3dd_from_accumulator_keeps_compensation() {
+        let mut acc = Vec3Accumulator::new();
+        acc.add(Vec3::new(1e16, 1e16, 1e16));
+        acc.add(Vec3::new(1.0, 0.5, 0.25));
+        let dd = Vec3DD::from(&acc);
+        assert_eq!(dd.hi(), Vec3::new(1e16, 1e16, 1e16));
+        assert_eq!(dd.lo(), Vec3::new(1.0, 0.5, 0.25));
+        assert_eq!(dd.to_vec3(), acc.resolve());
+    }
+
+    #[test]
+    fn vec3dd_dot_and_scale() {
+        let e = 2f64.powi(-30);
+        let a = Vec3DD::from(Vec3::new(1.0 + e, 1.0, 0.0));
+        let b = Vec3DD::from(Vec3::new(1.0 + e, -(1.0 + 2.0 * e), 0.0));
+        assert_eq!(a.dot(b).to_f64(), e * e);
+
+        let third = Vec3DD::from(Vec3::new(1.0, 2.0, 3.0)).scale(1.0 / 3.0);
+        assert_eq!(third.scale(3.0).to_vec3(), Vec3::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn vec3dd_to_from_le_bytes_roundtrip() {
+        let original = Vec3DD::new(
+            DoubleDouble::from_sum(1.0, 1e-20),
+            DoubleDouble::from_sum(-2.0, 3e-19),
+            DoubleDouble::from(0.5),
+        );
+        assert_eq!(Vec3DD::from_le_bytes(original.to_le_bytes()), original);
+    }
+}
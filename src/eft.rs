@@ -0,0 +1,69 @@
+//! Error-Free Transformations
+//!
+//! Building blocks for compensated arithmetic. Each transform returns the
+//! rounded floating-point result together with the exact rounding error, so
+//! that `a op b == result + error` holds exactly in real arithmetic.
+//!
+//! These are the primitives behind the `*_compensated` methods on the
+//! vector types. They are generic over [`Scalar`] and rely on IEEE-754
+//! round-to-nearest and on `mul_add` being a correctly-rounded fused
+//! multiply-add (guaranteed by `std`, with a software fallback on targets
+//! without hardware FMA).
+
+use crate::Scalar;
+
+/// Knuth's TwoSum: `a + b == s + e` exactly, with no precondition on magnitudes.
+#[inline]
+pub(crate) fn two_sum<T: Scalar>(a: T, b: T) -> (T, T) {
+    let s = a + b;
+    let bb = s - a;
+    let e = (a - (s - bb)) + (b - bb);
+    (s, e)
+}
+
+/// Dekker's FastTwoSum: `a + b == s + e` exactly, provided `|a| >= |b|`
+/// (or `a == 0`).
+#[inline]
+pub(crate) fn fast_two_sum<T: Scalar>(a: T, b: T) -> (T, T) {
+    let s = a + b;
+    let e = b - (s - a);
+    (s, e)
+}
+
+/// FMA-based TwoProduct: `a * b == p + e` exactly (barring overflow/underflow).
+#[inline]
+pub(crate) fn two_product<T: Scalar>(a: T, b: T) -> (T, T) {
+    let p = a * b;
+    let e = a.mul_add(b, -p);
+    (p, e)
+}
+
+/// Kahan's difference of products: `a * b - c * d` with at most 1.5 ulp error.
+///
+/// The rounding error of `c * d` is recovered with an FMA and added back,
+/// which avoids catastrophic cancellation when the two products nearly agree.
+#[inline]
+pub(crate) fn diff_of_products<T: Scalar>(a: T, b: T, c: T, d: T) -> T {
+    let w = c * d;
+    let e = (-c).mul_add(d, w);
+    let f = a.mul_add(b, -w);
+    f + e
+}
+
+/// Compensated dot product (Ogita–Rump–Oishi `Dot2`).
+///
+/// The result is as accurate as if it had been computed in twice the working
+/// precision and then rounded once.
+#[inline]
+pub(crate) fn dot2<T: Scalar>(a: &[T], b: &[T]) -> T {
+    debug_assert_eq!(a.len(), b.len());
+    let mut p = T::ZERO;
+    let mut s = T::ZERO;
+    for (&x, &y) in a.iter().zip(b) {
+        let (h, r) = two_product(x, y);
+        let (sum, q) = two_sum(p, h);
+        p = sum;
+        s += q + r;
+    }
+    p + s
+}
@@ -0,0 +1,571 @@
+//! Integer-only Q32.32 fixed-point vectors for lockstep targets.
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+use crate::Vec3;
+
+/// A signed Q32.32 fixed-point number
+///
+/// Stored as an `i64` counting units of 2^-32, giving a range of about
+/// ±2.1e9 with a resolution of about 2.3e-10. All arithmetic is integer
+/// arithmetic, so results are bit-identical on every compiler and target.
+///
+/// The operators and unprefixed methods wrap on overflow in every build
+/// profile (unlike plain `i64` arithmetic, which panics only in debug
+/// builds). Use the `checked_*` or `saturating_*` methods where overflow
+/// must be detected or clamped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct Fixed(i64);
+
+impl Fixed {
+    /// Number of fractional bits.
+    pub const FRAC_BITS: u32 = 32;
+
+    /// Zero.
+    pub const ZERO: Self = Self(0);
+
+    /// One.
+    pub const ONE: Self = Self(1 << Self::FRAC_BITS);
+
+    /// The smallest positive value, 2^-32.
+    pub const EPSILON: Self = Self(1);
+
+    /// The most negative value, -2^31.
+    pub const MIN: Self = Self(i64::MIN);
+
+    /// The largest value, 2^31 - 2^-32.
+    pub const MAX: Self = Self(i64::MAX);
+
+    /// Create a Fixed from its raw Q32.32 bits.
+    #[inline]
+    pub const fn from_bits(bits: i64) -> Self {
+        Self(bits)
+    }
+
+    /// The raw Q32.32 bits.
+    #[inline]
+    pub const fn to_bits(self) -> i64 {
+        self.0
+    }
+
+    /// Convert an integer. Every `i32` is representable.
+    #[inline]
+    pub const fn from_int(value: i32) -> Self {
+        Self((value as i64) << Self::FRAC_BITS)
+    }
+
+    /// Convert an f64 exactly, or `None` if it is out of range or has bits
+    /// below 2^-32.
+    #[inline]
+    pub fn from_f64(value: f64) -> Option<Self> {
+        let scaled = value * 2f64.powi(Self::FRAC_BITS as i32);
+        if scaled.fract() != 0.0 {
+            return None;
+        }
+        Self::from_scaled(scaled)
+    }
+
+    /// Convert an f64, rounding to the nearest multiple of 2^-32 (ties to
+    /// even). Returns `None` for NaN and values out of range.
+    #[inline]
+    pub fn from_f64_rounded(value: f64) -> Option<Self> {
+        Self::from_scaled((value * 2f64.powi(Self::FRAC_BITS as i32)).round_ties_even())
+    }
+
+    /// Convert to the nearest f64 (ties to even).
+    ///
+    /// Exact whenever the value has at most 53 significant bits, which
+    /// includes every value with magnitude below 2^21.
+    #[inline]
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 * 2f64.powi(-(Self::FRAC_BITS as i32))
+    }
+
+    /// Convert to f64 only if the conversion is exact.
+    #[inline]
+    pub fn to_f64_exact(self) -> Option<f64> {
+        let magnitude = self.0.unsigned_abs();
+        if magnitude == 0 || magnitude >> magnitude.trailing_zeros() < 1 << 53 {
+            Some(self.to_f64())
+        } else {
+            None
+        }
+    }
+
+    /// Checked addition.
+    #[inline]
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.0.checked_add(rhs.0).map(Self)
+    }
+
+    /// Saturating addition.
+    #[inline]
+    pub fn saturating_add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+
+    /// Checked subtraction.
+    #[inline]
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.0.checked_sub(rhs.0).map(Self)
+    }
+
+    /// Saturating subtraction.
+    #[inline]
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+
+    /// Checked multiplication, rounded to nearest (ties toward +∞).
+    #[inline]
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        narrow_checked(self.0 as i128 * rhs.0 as i128)
+    }
+
+    /// Saturating multiplication, rounded to nearest (ties toward +∞).
+    #[inline]
+    pub fn saturating_mul(self, rhs: Self) -> Self {
+        narrow_saturating(self.0 as i128 * rhs.0 as i128)
+    }
+
+    /// Checked negation. Only [`MIN`](Self::MIN) overflows.
+    #[inline]
+    pub fn checked_neg(self) -> Option<Self> {
+        self.0.checked_neg().map(Self)
+    }
+
+    /// `scaled` is the value in units of 2^-32; it must already be integral.
+    #[inline]
+    fn from_scaled(scaled: f64) -> Option<Self> {
+        // The range check is exact: both bounds are powers of two.
+        if scaled >= -(2f64.powi(63)) && scaled < 2f64.powi(63) {
+            Some(Self(scaled as i64))
+        } else {
+            None
+        }
+    }
+}
+
+impl From<i32> for Fixed {
+    fn from(value: i32) -> Self {
+        Self::from_int(value)
+    }
+}
+
+impl std::ops::Add for Fixed {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_add(rhs.0))
+    }
+}
+
+impl std::ops::Sub for Fixed {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+impl std::ops::Mul for Fixed {
+    type Output = Self;
+    /// Multiplication rounded to nearest (ties toward +∞), wrapping on overflow.
+    fn mul(self, rhs: Self) -> Self {
+        narrow_wrapping(self.0 as i128 * rhs.0 as i128)
+    }
+}
+
+impl std::ops::Neg for Fixed {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self(self.0.wrapping_neg())
+    }
+}
+
+/// A 3D vector with Q32.32 fixed-point components
+///
+/// Mirrors the [`Vec3`] API so that lockstep targets can switch
+/// representations behind a type alias, but every operation, including
+/// [`magnitude`](Self::magnitude), is integer-only and therefore identical
+/// on every compiler and target.
+///
+/// Products are formed exactly in 128 bits and rounded once, so
+/// [`dot`](Self::dot) and [`magnitude_squared`](Self::magnitude_squared)
+/// round once rather than per term. Operators and unprefixed methods wrap on
+/// overflow; `checked_*` methods return `None` and `saturating_*` methods
+/// clamp to [`Fixed::MIN`] / [`Fixed::MAX`] per component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct FixedVec3 {
+    pub x: Fixed,
+    pub y: Fixed,
+    pub z: Fixed,
+}
+
+impl FixedVec3 {
+    /// The zero vector.
+    pub const ZERO: Self = Self {
+        x: Fixed::ZERO,
+        y: Fixed::ZERO,
+        z: Fixed::ZERO,
+    };
+
+    /// Create a new FixedVec3.
+    #[inline]
+    pub const fn new(x: Fixed, y: Fixed, z: Fixed) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Convert a [`Vec3`] exactly, or `None` if any component is not
+    /// representable in Q32.32.
+    #[inline]
+    pub fn from_vec3(v: Vec3) -> Option<Self> {
+        Some(Self {
+            x: Fixed::from_f64(v.x)?,
+            y: Fixed::from_f64(v.y)?,
+            z: Fixed::from_f64(v.z)?,
+        })
+    }
+
+    /// Convert a [`Vec3`], rounding each component to nearest. Returns
+    /// `None` if any component is NaN or out of range.
+    #[inline]
+    pub fn from_vec3_rounded(v: Vec3) -> Option<Self> {
+        Some(Self {
+            x: Fixed::from_f64_rounded(v.x)?,
+            y: Fixed::from_f64_rounded(v.y)?,
+            z: Fixed::from_f64_rounded(v.z)?,
+        })
+    }
+
+    /// Convert to a [`Vec3`] exactly, or `None` if any component needs
+    /// more than 53 significant bits.
+    #[inline]
+    pub fn to_vec3(&self) -> Option<Vec3> {
+        Some(Vec3::new(
+            self.x.to_f64_exact()?,
+            self.y.to_f64_exact()?,
+            self.z.to_f64_exact()?,
+        ))
+    }
+
+    /// Convert to a [`Vec3`], rounding each component to the nearest f64.
+    #[inline]
+    pub fn to_vec3_rounded(&self) -> Vec3 {
+        Vec3::new(self.x.to_f64(), self.y.to_f64(), self.z.to_f64())
+    }
+
+    /// Returns the raw Q32.32 bits of each component as little-endian `i64`s.
+    ///
+    /// Same size and layout as [`Vec3::to_le_bytes`], so hashes can be
+    /// computed the same way whichever representation is in use.
+    #[inline]
+    pub fn to_le_bytes(&self) -> [u8; 24] {
+        let mut buf = [0u8; 24];
+        buf[0..8].copy_from_slice(&self.x.to_bits().to_le_bytes());
+        buf[8..16].copy_from_slice(&self.y.to_bits().to_le_bytes());
+        buf[16..24].copy_from_slice(&self.z.to_bits().to_le_bytes());
+        buf
+    }
+
+    /// Reconstruct a FixedVec3 from little-endian bytes.
+    ///
+    /// This is the inverse of [`to_le_bytes`](Self::to_le_bytes).
+    #[inline]
+    pub fn from_le_bytes(bytes: [u8; 24]) -> Self {
+        let component = |i: usize| {
+            Fixed::from_bits(i64::from_le_bytes(
+                bytes[i * 8..i * 8 + 8].try_into().unwrap(),
+            ))
+        };
+        Self {
+            x: component(0),
+            y: component(1),
+            z: component(2),
+        }
+    }
+
+    /// Compute the dot product, rounded once and wrapping on overflow.
+    #[inline]
+    pub fn dot(&self, other: FixedVec3) -> Fixed {
+        narrow_wrapping(self.wrapping_wide_dot(other))
+    }
+
+    /// Compute the dot product, or `None` on overflow.
+    #[inline]
+    pub fn checked_dot(&self, other: FixedVec3) -> Option<Fixed> {
+        narrow_checked(self.wide_dot(other)?)
+    }
+
+    /// Compute the dot product, clamping on overflow.
+    #[inline]
+    pub fn saturating_dot(&self, other: FixedVec3) -> Fixed {
+        let [a, b, c] = self.wide_products(other);
+        narrow_saturating(a.saturating_add(b).saturating_add(c))
+    }
+
+    /// Compute the squared magnitude, wrapping on overflow.
+    #[inline]
+    pub fn magnitude_squared(&self) -> Fixed {
+        self.dot(*self)
+    }
+
+    /// Compute the squared magnitude, or `None` on overflow.
+    #[inline]
+    pub fn checked_magnitude_squared(&self) -> Option<Fixed> {
+        self.checked_dot(*self)
+    }
+
+    /// Compute the squared magnitude, clamping to [`Fixed::MAX`] on overflow.
+    #[inline]
+    pub fn saturating_magnitude_squared(&self) -> Fixed {
+        self.saturating_dot(*self)
+    }
+
+    /// Compute the magnitude, rounded down to a multiple of 2^-32 and
+    /// wrapping on overflow.
+    ///
+    /// The squared magnitude is kept exact in 128 bits and passed to an
+    /// integer square root, so there is no intermediate rounding.
+    #[inline]
+    pub fn magnitude(&self) -> Fixed {
+        Fixed::from_bits(self.wide_magnitude() as i64)
+    }
+
+    /// Compute the magnitude, or `None` if it exceeds [`Fixed::MAX`].
+    #[inline]
+    pub fn checked_magnitude(&self) -> Option<Fixed> {
+        i64::try_from(self.wide_magnitude())
+            .ok()
+            .map(Fixed::from_bits)
+    }
+
+    /// Compute the magnitude, clamping to [`Fixed::MAX`].
+    #[inline]
+    pub fn saturating_magnitude(&self) -> Fixed {
+        self.checked_magnitude().unwrap_or(Fixed::MAX)
+    }
+
+    /// Scale by a scalar, wrapping on overflow.
+    #[inline]
+    pub fn scale(&self, scalar: Fixed) -> Self {
+        Self {
+            x: self.x * scalar,
+            y: self.y * scalar,
+            z: self.z * scalar,
+        }
+    }
+
+    /// Scale by a scalar, or `None` on overflow.
+    #[inline]
+    pub fn checked_scale(&self, scalar: Fixed) -> Option<Self> {
+        Some(Self {
+            x: self.x.checked_mul(scalar)?,
+            y: self.y.checked_mul(scalar)?,
+            z: self.z.checked_mul(scalar)?,
+        })
+    }
+
+    /// Scale by a scalar, clamping each component on overflow.
+    #[inline]
+    pub fn saturating_scale(&self, scalar: Fixed) -> Self {
+        Self {
+            x: self.x.saturating_mul(scalar),
+            y: self.y.saturating_mul(scalar),
+            z: self.z.saturating_mul(scalar),
+        }
+    }
+
+    /// Component-wise addition, or `None` on overflow.
+    #[inline]
+    pub fn checked_add(&self, rhs: FixedVec3) -> Option<Self> {
+        Some(Self {
+            x: self.x.checked_add(rhs.x)?,
+            y: self.y.checked_add(rhs.y)?,
+            z: self.z.checked_add(rhs.z)?,
+        })
+    }
+
+    /// Component-wise addition, clamping each component on overflow.
+    #[inline]
+    pub fn saturating_add(&self, rhs: FixedVec3) -> Self {
+        Self {
+            x: self.x.saturating_add(rhs.x),
+            y: self.y.saturating_add(rhs.y),
+            z: self.z.saturating_add(rhs.z),
+        }
+    }
+
+    /// Component-wise subtraction, or `None` on overflow.
+    #[inline]
+    pub fn checked_sub(&self, rhs: FixedVec3) -> Option<Self> {
+        Some(Self {
+            x: self.x.checked_sub(rhs.x)?,
+            y: self.y.checked_sub(rhs.y)?,
+            z: self.z.checked_sub(rhs.z)?,
+        })
+    }
+
+    /// Component-wise subtraction, clamping each component on overflow.
+    #[inline]
+    pub fn saturating_sub(&self, rhs: FixedVec3) -> Self {
+        Self {
+            x: self.x.saturating_sub(rhs.x),
+            y: self.y.saturating_sub(rhs.y),
+            z: self.z.saturating_sub(rhs.z),
+        }
+    }
+
+    /// The exact Q64.64 products of each component pair.
+    #[inline]
+    fn wide_products(&self, other: FixedVec3) -> [i128; 3] {
+        [
+            self.x.to_bits() as i128 * other.x.to_bits() as i128,
+            self.y.to_bits() as i128 * other.y.to_bits() as i128,
+            self.z.to_bits() as i128 * other.z.to_bits() as i128,
+        ]
+    }
+
+    /// The exact Q64.64 dot product, or `None` if it overflows `i128`
+    /// (in which case it also overflows the Q32.32 result).
+    #[inline]
+    fn wide_dot(&self, other: FixedVec3) -> Option<i128> {
+        let [a, b, c] = self.wide_products(other);
+        a.checked_add(b)?.checked_add(c)
+    }
+
+    #[inline]
+    fn wrapping_wide_dot(&self, other: FixedVec3) -> i128 {
+        let [a, b, c] = self.wide_products(other);
+        a.wrapping_add(b).wrapping_add(c)
+    }
+
+    /// `floor(sqrt(x² + y² + z²))` in units of 2^-32. The sum of squares is
+    /// below 3·2^126, so it always fits in a `u128`.
+    #[inline]
+    fn wide_magnitude(&self) -> u128 {
+        let [a, b, c] = self.wide_products(*self);
+        (a as u128 + b as u128 + c as u128).isqrt()
+    }
+}
+
+impl std::ops::Add for FixedVec3 {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+
+impl std::ops::Sub for FixedVec3 {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+impl std::ops::Neg for FixedVec3 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+/// Round a Q64.64 value to Q32.32 (nearest, ties toward +∞).
+#[inline]
+fn round_wide(wide: i128) -> i128 {
+    (wide >> Fixed::FRAC_BITS) + ((wide >> (Fixed::FRAC_BITS - 1)) & 1)
+}
+
+#[inline]
+fn narrow_wrapping(wide: i128) -> Fixed {
+    Fixed::from_bits(round_wide(wide) as i64)
+}
+
+#[inline]
+fn narrow_checked(wide: i128) -> Option<Fixed> {
+    i64::try_from(round_wide(wide)).ok().map(Fixed::from_bits)
+}
+
+#[inline]
+fn narrow_saturating(wide: i128) -> Fixed {
+    let rounded = round_wide(wide);
+    Fixed::from_bits(rounded.clamp(i64::MIN as i128, i64::MAX as i128) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_vec3_roundtrips_vec3_when_representable() {
+        let v = Vec3::new(1.5, -1024.25, 2f64.powi(-32));
+        let f = FixedVec3::from_vec3(v).unwrap();
+        assert_eq!(f.to_vec3(), Some(v));
+        assert_eq!(FixedVec3::from_le_bytes(f.to_le_bytes()), f);
+
+        // 0.1 has bits below 2^-32; 2^40 is out of range.
+        assert_eq!(FixedVec3::from_vec3(Vec3::new(0.1, 0.0, 0.0)), None);
+        assert_eq!(
+            FixedVec3::from_vec3(Vec3::new(0.0, 2f64.powi(40), 0.0)),
+            None
+        );
+        let rounded = FixedVec3::from_vec3_rounded(Vec3::new(0.1, 0.0, 0.0)).unwrap();
+        assert!((rounded.to_vec3_rounded().x - 0.1).abs() < 2f64.powi(-33));
+
+        // 2^30 + 2^-32 needs 63 significant bits, too many for an f64.
+        let wide = Fixed::from_int(1 << 30) + Fixed::EPSILON;
+        assert_eq!(wide.to_f64_exact(), None);
+        assert_eq!(wide.to_f64(), 2f64.powi(30));
+    }
+
+    #[test]
+    fn fixed_vec3_arithmetic_matches_f64() {
+        let a = FixedVec3::from_vec3(Vec3::new(3.0, -4.0, 0.5)).unwrap();
+        let b = FixedVec3::from_vec3(Vec3::new(0.25, 2.0, -8.0)).unwrap();
+        assert_eq!(a.dot(b), Fixed::from_f64(-11.25).unwrap());
+        assert_eq!(
+            a.scale(Fixed::from_f64(0.5).unwrap()).to_vec3(),
+            Some(Vec3::new(1.5, -2.0, 0.25))
+        );
+        let c = FixedVec3::new(Fixed::from_int(3), Fixed::from_int(4), Fixed::ZERO);
+        assert_eq!(c.magnitude(), Fixed::from_int(5));
+        assert_eq!(c.magnitude_squared(), Fixed::from_int(25));
+        assert_eq!((a + b - b), a);
+        assert_eq!(-(-a), a);
+    }
+
+    #[test]
+    fn fixed_vec3_overflow_modes() {
+        let big = FixedVec3::new(Fixed::MAX, Fixed::from_int(1), Fixed::MIN);
+        let two = Fixed::from_int(2);
+
+        assert_eq!(big.checked_scale(two), None);
+        let saturated = big.saturating_scale(two);
+        assert_eq!(saturated, FixedVec3::new(Fixed::MAX, two, Fixed::MIN));
+
+        assert_eq!(big.checked_add(big), None);
+        assert_eq!(big.saturating_add(big).x, Fixed::MAX);
+        assert_eq!(big.checked_sub(-big), None);
+
+        assert_eq!(big.checked_magnitude_squared(), None);
+        assert_eq!(big.saturating_magnitude_squared(), Fixed::MAX);
+        assert_eq!(big.checked_magnitude(), None);
+        assert_eq!(big.saturating_magnitude(), Fixed::MAX);
+
+        // Wrapping is deterministic in every build profile.
+        assert_eq!(Fixed::MAX + Fixed::EPSILON, Fixed::MIN);
+    }
+}
@@ -0,0 +1,354 @@
+//! Symplectic integrators built on [`Vec3Accumulator`].
+//!
+//! Each integrator advances a [`State`] whose position and velocity are
+//! compensated accumulators, so the only error left is the method's own
+//! truncation error, not rounding drift. All four are symplectic: for
+//! conservative forces the energy error stays bounded over arbitrarily many
+//! steps instead of growing, which is what keeps orbits closed.
+//!
+//! | Integrator            | Order | Force evaluations per step |
+//! |-----------------------|-------|----------------------------|
+//! | [`SemiImplicitEuler`] | 1     | 1                          |
+//! | [`VelocityVerlet`]    | 2     | 1 (reuses the previous)    |
+//! | [`Leapfrog`]          | 2     | 1                          |
+//! | [`Yoshida4`]          | 4     | 3                          |
+//!
+//! The force callback maps a position to an acceleration (force divided by
+//! mass). Symplecticity requires that it depend on position only.
+//!
+//! For non-Hamiltonian systems (drag, controllers), the explicit
+//! Runge–Kutta solvers [`Rk4`] and the adaptive [`DormandPrince`] advance
+//! any `y' = f(t, y)` over an [`OdeState`], with `f64`, [`Vec3`] or
+//! [`VecN`](crate::VecN) values.
+//!
+//! # Example
+//!
+//! ```rust
+//! use drift_linalg::integrate::{Integrator, State, Yoshida4};
+//! use drift_linalg::Vec3;
+//!
+//! // A circular Kepler orbit with GM = 1, radius 1 and period 2π.
+//! let gravity = |x: Vec3| {
+//!     let r2 = x.magnitude_squared();
+//!     x.scale(-1.0 / (r2 * r2.sqrt()))
+//! };
+//! let mut orbit = State::new(Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+//!
+//! let dt = std::f64::consts::TAU / 1000.0;
+//! Yoshida4.steps(&mut orbit, dt, 100 * 1000, gravity);
+//!
+//! // After 100 orbits the body is back where it started.
+//! let p = orbit.position();
+//! assert!((p - Vec3::new(1.0, 0.0, 0.0)).magnitude() < 1e-6);
+//! ```
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Vec3, Vec3Accumulator};
+
+mod runge_kutta;
+
+pub use runge_kutta::{DormandPrince, OdeState, OdeVector, Rk4, StepReport};
+
+/// Position and velocity of a point mass, each held in a
+/// [`Vec3Accumulator`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct State {
+    position: Vec3Accumulator,
+    velocity: Vec3Accumulator,
+    /// The acceleration at the current position, if the last step computed
+    /// it. Only [`VelocityVerlet`] fills and reads this; see
+    /// [`State::invalidate_acceleration`].
+    acceleration: Option<Vec3>,
+}
+
+impl State {
+    /// Create a state from an initial position and velocity.
+    #[inline]
+    pub fn new(position: Vec3, velocity: Vec3) -> Self {
+        Self {
+            position: Vec3Accumulator::with_initial(position),
+            velocity: Vec3Accumulator::with_initial(velocity),
+            acceleration: None,
+        }
+    }
+
+    /// The current position.
+    #[inline]
+    pub fn position(&self) -> Vec3 {
+        self.position.resolve()
+    }
+
+    /// The current velocity.
+    #[inline]
+    pub fn velocity(&self) -> Vec3 {
+        self.velocity.resolve()
+    }
+
+    /// The position accumulator, including its compensation.
+    #[inline]
+    pub fn position_accumulator(&self) -> &Vec3Accumulator {
+        &self.position
+    }
+
+    /// The velocity accumulator, including its compensation.
+    #[inline]
+    pub fn velocity_accumulator(&self) -> &Vec3Accumulator {
+        &self.velocity
+    }
+
+    /// Forget the acceleration cached by [`VelocityVerlet`].
+    ///
+    /// Call this when the force field changes between steps (a body was
+    /// added, a parameter was tuned, a different callback is passed), so the
+    /// next step evaluates the acceleration afresh instead of reusing the
+    /// one computed with the old field.
+    #[inline]
+    pub fn invalidate_acceleration(&mut self) {
+        self.acceleration = None;
+    }
+
+    /// `x += v · h`
+    #[inline]
+    fn drift(&mut self, h: f64) {
+        self.position.add_scaled_exact(self.velocity.resolve(), h);
+    }
+
+    /// `v += a · h`
+    #[inline]
+    fn kick(&mut self, acceleration: Vec3, h: f64) {
+        self.velocity.add_scaled_exact(acceleration, h);
+    }
+}
+
+/// A fixed-step integrator for [`State`]
+pub trait Integrator {
+    /// Advance `state` by `dt`.
+    ///
+    /// `acceleration` maps a position to the acceleration at that position.
+    fn step<F: FnMut(Vec3) -> Vec3>(&self, state: &mut State, dt: f64, acceleration: F);
+
+    /// Advance `state` by `steps` steps of `dt`.
+    fn steps<F: FnMut(Vec3) -> Vec3>(
+        &self,
+        state: &mut State,
+        dt: f64,
+        steps: usize,
+        mut acceleration: F,
+    ) {
+        for _ in 0..steps {
+            self.step(state, dt, &mut acceleration);
+        }
+    }
+}
+
+/// Semi-implicit (symplectic) Euler: kick, then drift with the new velocity
+///
+/// First order, but unlike explicit Euler it does not pump energy into
+/// orbits. The cheapest choice for games and particles.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SemiImplicitEuler;
+
+impl Integrator for SemiImplicitEuler {
+    fn step<F: FnMut(Vec3) -> Vec3>(&self, state: &mut State, dt: f64, mut acceleration: F) {
+        state.acceleration = None;
+        let a = acceleration(state.position());
+        state.kick(a, dt);
+        state.drift(dt);
+    }
+}
+
+/// Velocity Verlet (kick-drift-kick)
+///
+/// Second order and time-reversible. The acceleration at the end of each
+/// step is kept in the [`State`] and reused at the start of the next, so
+/// after the first step it costs one force evaluation per step. If the force
+/// field changes between steps, call [`State::invalidate_acceleration`]
+/// first.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VelocityVerlet;
+
+impl Integrator for VelocityVerlet {
+    fn step<F: FnMut(Vec3) -> Vec3>(&self, state: &mut State, dt: f64, mut acceleration: F) {
+        let a = match state.acceleration.take() {
+            Some(a) => a,
+            None => acceleration(state.position()),
+        };
+        state.kick(a, 0.5 * dt);
+        state.drift(dt);
+        let a = acceleration(state.position());
+        state.kick(a, 0.5 * dt);
+        state.acceleration = Some(a);
+    }
+}
+
+/// Leapfrog in drift-kick-drift form
+///
+/// The same second-order scheme as [`VelocityVerlet`] with the half steps
+/// on the position instead; one force evaluation per step and no cached
+/// state.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Leapfrog;
+
+impl Integrator for Leapfrog {
+    fn step<F: FnMut(Vec3) -> Vec3>(&self, state: &mut State, dt: f64, mut acceleration: F) {
+        state.acceleration = None;
+        state.drift(0.5 * dt);
+        let a = acceleration(state.position());
+        state.kick(a, dt);
+        state.drift(0.5 * dt);
+    }
+}
+
+/// Yoshida's fourth-order symplectic integrator
+///
+/// Three leapfrog substeps with weights `w₁, w₀, w₁`, where
+/// `w₁ = 1 / (2 - 2^(1/3))` and `w₀ = 1 - 2w₁` (H. Yoshida, "Construction of
+/// higher order symplectic integrators", 1990). The weights are stored as
+/// literals rather than computed with `cbrt`, which is not guaranteed to be
+/// correctly rounded on every platform.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Yoshida4;
+
+impl Yoshida4 {
+    /// `w₁ = 1 / (2 - 2^(1/3))`
+    const W1: f64 = 1.351_207_191_959_657_6;
+    /// `w₀ = -2^(1/3) / (2 - 2^(1/3))`
+    const W0: f64 = -1.702_414_383_919_315_3;
+    /// Drift weights `c₁ = c₄` and `c₂ = c₃`.
+    const C: [f64; 4] = [
+        Self::W1 / 2.0,
+        (Self::W0 + Self::W1) / 2.0,
+        (Self::W0 + Self::W1) / 2.0,
+        Self::W1 / 2.0,
+    ];
+    /// Kick weights `d₁ = d₃` and `d₂`.
+    const D: [f64; 3] = [Self::W1, Self::W0, Self::W1];
+}
+
+impl Integrator for Yoshida4 {
+    fn step<F: FnMut(Vec3) -> Vec3>(&self, state: &mut State, dt: f64, mut acceleration: F) {
+        state.acceleration = None;
+        for (c, d) in Self::C.iter().zip(Self::D) {
+            state.drift(c * dt);
+            let a = acceleration(state.position());
+            state.kick(a, d * dt);
+        }
+        state.drift(Self::C[3] * dt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gravity(x: Vec3) -> Vec3 {
+        let r2 = x.magnitude_squared();
+        x.scale(-1.0 / (r2 * r2.sqrt()))
+    }
+
+    fn kepler_energy(state: &State) -> f64 {
+        0.5 * state.velocity().magnitude_squared() - 1.0 / state.position().magnitude()
+    }
+
+    /// Maximum relative energy error, sampled every step, over `orbits`
+    /// orbits of an eccentric (e = 0.5) Kepler orbit.
+    fn max_energy_error<I: Integrator>(
+        integrator: I,
+        steps_per_orbit: usize,
+        orbits: usize,
+    ) -> f64 {
+        let mut state = State::new(Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.5f64.sqrt(), 0.0));
+        let e0 = kepler_energy(&state);
+        // Semi-major axis a = 2, so the period is 2π · a^(3/2).
+        let dt = std::f64::consts::TAU * 8f64.sqrt() / steps_per_orbit as f64;
+        let mut worst: f64 = 0.0;
+        for _ in 0..orbits * steps_per_orbit {
+            integrator.step(&mut state, dt, gravity);
+            worst = worst.max(((kepler_energy(&state) - e0) / e0).abs());
+        }
+        worst
+    }
+
+    #[test]
+    fn integrators_conserve_energy_without_secular_drift() {
+        // Bounded error: 10x more orbits must not grow the worst case.
+        for (short, long) in [
+            (
+                max_energy_error(SemiImplicitEuler, 2000, 10),
+                max_energy_error(SemiImplicitEuler, 2000, 100),
+            ),
+            (
+                max_energy_error(VelocityVerlet, 2000, 10),
+                max_energy_error(VelocityVerlet, 2000, 100),
+            ),
+            (
+                max_energy_error(Leapfrog, 2000, 10),
+                max_energy_error(Leapfrog, 2000, 100),
+            ),
+            (
+                max_energy_error(Yoshida4, 2000, 10),
+                max_energy_error(Yoshida4, 2000, 100),
+            ),
+        ] {
+            assert!(long < short * 1.1, "{short} -> {long}");
+        }
+    }
+
+    #[test]
+    fn integrators_have_expected_order() {
+        // Halving the step divides the error by about 2^order.
+        let ratio = |e: fn(usize) -> f64| e(1000) / e(2000);
+        let euler = ratio(|n| max_energy_error(SemiImplicitEuler, n, 1));
+        let verlet = ratio(|n| max_energy_error(VelocityVerlet, n, 1));
+        let leapfrog = ratio(|n| max_energy_error(Leapfrog, n, 1));
+        let yoshida = ratio(|n| max_energy_error(Yoshida4, n, 1));
+        assert!((1.5..3.0).contains(&euler), "{euler}");
+        assert!((3.0..5.0).contains(&verlet), "{verlet}");
+        assert!((3.0..5.0).contains(&leapfrog), "{leapfrog}");
+        assert!((12.0..20.0).contains(&yoshida), "{yoshida}");
+    }
+
+    #[test]
+    fn velocity_verlet_reuses_acceleration() {
+        let mut calls = 0;
+        let mut state = State::new(Vec3::new(1.0, 0.0, 0.0), Vec3::ZERO);
+        VelocityVerlet.steps(&mut state, 0.01, 100, |x| {
+            calls += 1;
+            -x
+        });
+        assert_eq!(calls, 101);
+
+        // Harmonic oscillator: x(t) = cos t.
+        assert!((state.position().x - 1f64.cos()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn velocity_verlet_recomputes_invalidated_acceleration() {
+        let mut state = State::new(Vec3::new(1.0, 0.0, 0.0), Vec3::ZERO);
+        VelocityVerlet.step(&mut state, 0.01, |x| -x);
+
+        // Switch the field off: the stale -x must not be kicked in.
+        let velocity = state.velocity();
+        state.invalidate_acceleration();
+        let mut calls = 0;
+        VelocityVerlet.step(&mut state, 0.01, |_| {
+            calls += 1;
+            Vec3::ZERO
+        });
+        assert_eq!(calls, 2);
+        assert_eq!(state.velocity(), velocity);
+    }
+
+    #[test]
+    fn yoshida_is_time_reversible() {
+        let start = State::new(Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.2, 0.1));
+        let mut state = start.clone();
+        Yoshida4.steps(&mut state, 0.01, 1000, gravity);
+        Yoshida4.steps(&mut state, -0.01, 1000, gravity);
+        assert!((state.position() - start.position()).magnitude() < 1e-10);
+        assert!((state.velocity() - start.velocity()).magnitude() < 1e-10);
+    }
+}
@@ -0,0 +1,676 @@
+//! Explicit Runge–Kutta solvers for general first-order systems.
+
+use crate::{Accumulator, CompensatedAccumulate, Vec3, VecN};
+
+/// A state vector that the Runge–Kutta solvers can advance
+///
+/// Stage combinations are formed in an [`Accumulator<T>`], so any
+/// [`CompensatedAccumulate`] type works; the component view is only used
+/// for the adaptive error norm.
+pub trait OdeVector: CompensatedAccumulate + Copy {
+    /// Number of components.
+    const DIM: usize;
+
+    /// Component `index`, for `index < DIM`.
+    fn component(&self, index: usize) -> f64;
+}
+
+impl OdeVector for f64 {
+    const DIM: usize = 1;
+
+    #[inline]
+    fn component(&self, _index: usize) -> f64 {
+        *self
+    }
+}
+
+impl OdeVector for Vec3 {
+    const DIM: usize = 3;
+
+    #[inline]
+    fn component(&self, index: usize) -> f64 {
+        self[index]
+    }
+}
+
+impl<const N: usize> OdeVector for VecN<N> {
+    const DIM: usize = N;
+
+    #[inline]
+    fn component(&self, index: usize) -> f64 {
+        self[index]
+    }
+}
+
+/// Time and state of a system `y' = f(t, y)`, both accumulated with
+/// compensation
+///
+/// Keeping `t` compensated matters as much as `y`: a million steps of
+/// `h = 0.1` summed naively put the clock visibly off, and every stage
+/// then evaluates `f` at the wrong time.
+#[derive(Debug, Clone)]
+pub struct OdeState<T: OdeVector> {
+    t: Accumulator<f64>,
+    y: Accumulator<T>,
+    /// `f(t, y)` at the current point, if the last step computed it. Only
+    /// [`DormandPrince`] fills and reads this; see
+    /// [`OdeState::invalidate_derivative`].
+    derivative: Option<T>,
+}
+
+impl<T: OdeVector> OdeState<T> {
+    /// Create a state at time `t` with value `y`.
+    #[inline]
+    pub fn new(t: f64, y: T) -> Self {
+        Self {
+            t: Accumulator::with_initial(t),
+            y: Accumulator::with_initial(y),
+            derivative: None,
+        }
+    }
+
+    /// The current time.
+    #[inline]
+    pub fn t(&self) -> f64 {
+        self.t.resolve()
+    }
+
+    /// The current state.
+    #[inline]
+    pub fn y(&self) -> T {
+        self.y.resolve()
+    }
+
+    /// Forget the derivative cached by [`DormandPrince`].
+    ///
+    /// Call this when `f` changes between steps (a parameter was tuned, a
+    /// different closure is passed), so the next step evaluates its first
+    /// stage afresh instead of reusing `f(t, y)` from the old system.
+    #[inline]
+    pub fn invalidate_derivative(&mut self) {
+        self.derivative = None;
+    }
+
+    /// `y + Σ hᵢ kᵢ`, formed without resolving `y` first.
+    #[inline]
+    fn offset(&self, terms: &[(f64, T)]) -> T {
+        let mut acc = self.y.clone();
+        for &(h, k) in terms {
+            acc.add_scaled(k, h);
+        }
+        acc.resolve()
+    }
+
+    #[inline]
+    fn advance(&mut self, h: f64, terms: &[(f64, T)]) {
+        for &(w, k) in terms {
+            self.y.add_scaled(k, w);
+        }
+        self.t.add(h);
+    }
+}
+
+/// The classical fourth-order Runge–Kutta method
+///
+/// Fixed step, four evaluations of `f` per step.
+///
+/// # Example
+///
+/// ```rust
+/// use drift_linalg::integrate::{OdeState, Rk4};
+///
+/// // Exponential decay y' = -y, y(0) = 1.
+/// let mut state = OdeState::new(0.0, 1.0);
+/// for _ in 0..1000 {
+///     Rk4.step(&mut state, 0.001, |_, y| -y);
+/// }
+/// assert!((state.y() - (-1.0f64).exp()).abs() < 1e-12);
+/// assert_eq!(state.t(), 1.0);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rk4;
+
+impl Rk4 {
+    /// Advance `state` by `h`.
+    pub fn step<T, F>(&self, state: &mut OdeState<T>, h: f64, mut f: F)
+    where
+        T: OdeVector,
+        F: FnMut(f64, T) -> T,
+    {
+        state.derivative = None;
+        let t = state.t();
+        let k1 = f(t, state.y());
+        let k2 = f(t + 0.5 * h, state.offset(&[(0.5 * h, k1)]));
+        let k3 = f(t + 0.5 * h, state.offset(&[(0.5 * h, k2)]));
+        let k4 = f(t + h, state.offset(&[(h, k3)]));
+        let (h6, h3) = (h / 6.0, h / 3.0);
+        state.advance(h, &[(h6, k1), (h3, k2), (h3, k3), (h6, k4)]);
+    }
+}
+
+/// The outcome of one adaptive step attempt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepReport {
+    /// Whether the step was accepted and the state advanced.
+    pub accepted: bool,
+    /// The step size that was attempted.
+    pub h: f64,
+    /// The estimated local error divided by the tolerance, in the max norm.
+    /// The step is accepted when this is at most 1; it is NaN if any
+    /// component of the estimate is.
+    pub error: f64,
+    /// The step size to try next.
+    pub h_next: f64,
+}
+
+/// The Dormand–Prince 5(4) adaptive method
+///
+/// Each step computes a fifth-order solution and an embedded fourth-order
+/// one; their difference estimates the local error, which is compared
+/// against `atol + rtol · |y|` per component. Accepted steps propagate the
+/// fifth-order solution, and the last stage is reused as the first stage
+/// of the next step (FSAL), so an accepted step costs six evaluations.
+///
+/// Step-size control uses only `+ - * /` and a fixed-iteration fifth root,
+/// never `powf`, so the sequence of step sizes, and therefore the whole
+/// trajectory, is bit-identical across platforms.
+///
+/// # Example
+///
+/// ```rust
+/// use drift_linalg::integrate::{DormandPrince, OdeState};
+/// use drift_linalg::Vec3;
+///
+/// // A projectile with quadratic drag: v' = g - k|v|v.
+/// let drag = |_t: f64, v: Vec3| Vec3::new(0.0, 0.0, -9.81) - v * (0.05 * v.magnitude());
+///
+/// let solver = DormandPrince::new(1e-10, 1e-12).unwrap();
+/// let mut velocity = OdeState::new(0.0, Vec3::new(30.0, 0.0, 30.0));
+/// solver.integrate_to(&mut velocity, 10.0, 0.01, drag).unwrap();
+///
+/// // Close to terminal velocity: g = k|v|², straight down.
+/// let v = velocity.y();
+/// assert_eq!(velocity.t(), 10.0);
+/// assert!((v.z + (9.81f64 / 0.05).sqrt()).abs() < 1e-3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DormandPrince {
+    rtol: f64,
+    atol: f64,
+    h_min: f64,
+    h_max: f64,
+}
+
+impl DormandPrince {
+    const C: [f64; 6] = [1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+    const A2: [f64; 1] = [1.0 / 5.0];
+    const A3: [f64; 2] = [3.0 / 40.0, 9.0 / 40.0];
+    const A4: [f64; 3] = [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0];
+    const A5: [f64; 4] = [
+        19372.0 / 6561.0,
+        -25360.0 / 2187.0,
+        64448.0 / 6561.0,
+        -212.0 / 729.0,
+    ];
+    const A6: [f64; 5] = [
+        9017.0 / 3168.0,
+        -355.0 / 33.0,
+        46732.0 / 5247.0,
+        49.0 / 176.0,
+        -5103.0 / 18656.0,
+    ];
+    /// Fifth-order weights; also the last row of the tableau (FSAL).
+    const B: [f64; 6] = [
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+    ];
+    /// Fifth- minus fourth-order weights, for the error estimate.
+    const E: [f64; 7] = [
+        71.0 / 57600.0,
+        0.0,
+        -71.0 / 16695.0,
+        71.0 / 1920.0,
+        -17253.0 / 339200.0,
+        22.0 / 525.0,
+        -1.0 / 40.0,
+    ];
+
+    /// Safety factor applied to the optimal step size.
+    const SAFETY: f64 = 0.9;
+    /// Bounds on the ratio between successive step sizes.
+    const MIN_FACTOR: f64 = 0.2;
+    const MAX_FACTOR: f64 = 5.0;
+
+    /// Create a solver with the given tolerances and no step-size limits.
+    ///
+    /// Returns `None` unless `rtol` is finite and non-negative and `atol` is
+    /// finite and positive. A zero `atol` would leave no tolerance for
+    /// components at zero, where the error ratio becomes `0 / 0` and every
+    /// step is rejected.
+    #[inline]
+    pub fn new(rtol: f64, atol: f64) -> Option<Self> {
+        if !(rtol >= 0.0 && rtol.is_finite() && atol > 0.0 && atol.is_finite()) {
+            return None;
+        }
+        Some(Self {
+            rtol,
+            atol,
+            h_min: 0.0,
+            h_max: f64::INFINITY,
+        })
+    }
+
+    /// Limit the step magnitude to `h_min..=h_max`.
+    ///
+    /// The controller never proposes a step longer than `h_max`, and
+    /// [`integrate_to`](Self::integrate_to) gives up once it would need one
+    /// shorter than `h_min`. Returns `None` unless `h_min` is finite and
+    /// non-negative and `h_max` is positive (possibly infinite) and at least
+    /// `h_min`.
+    #[inline]
+    pub fn with_step_limits(self, h_min: f64, h_max: f64) -> Option<Self> {
+        if !(h_min >= 0.0 && h_min.is_finite() && h_max > 0.0 && h_max >= h_min) {
+            return None;
+        }
+        Some(Self {
+            h_min,
+            h_max,
+            ..self
+        })
+    }
+
+    /// Relative tolerance.
+    #[inline]
+    pub fn rtol(&self) -> f64 {
+        self.rtol
+    }
+
+    /// Absolute tolerance.
+    #[inline]
+    pub fn atol(&self) -> f64 {
+        self.atol
+    }
+
+    /// Smallest step magnitude [`integrate_to`](Self::integrate_to) will
+    /// try before giving up.
+    #[inline]
+    pub fn h_min(&self) -> f64 {
+        self.h_min
+    }
+
+    /// Largest step magnitude the controller will propose.
+    #[inline]
+    pub fn h_max(&self) -> f64 {
+        self.h_max
+    }
+
+    /// Attempt one step of size `h`, which may be negative.
+    ///
+    /// If the estimated error is within tolerance the state is advanced;
+    /// otherwise it is left unchanged. Either way the report carries the
+    /// error estimate and the step size to try next. A NaN or infinite
+    /// error estimate, e.g. because `f` returned NaN, rejects the step and
+    /// shrinks `h` by the largest allowed factor.
+    ///
+    /// The first stage is reused from the previous call on the same `state`
+    /// (FSAL, or the rejected attempt's), so `f` must be the same system
+    /// each time. After changing it, call
+    /// [`OdeState::invalidate_derivative`].
+    pub fn step<T, F>(&self, state: &mut OdeState<T>, h: f64, mut f: F) -> StepReport
+    where
+        T: OdeVector,
+        F: FnMut(f64, T) -> T,
+    {
+        let t = state.t();
+        let y = state.y();
+        let k1 = match state.derivative {
+            Some(k) => k,
+            None => f(t, y),
+        };
+        let k2 = f(t + Self::C[0] * h, state.offset(&stage(h, Self::A2, [k1])));
+        let k3 = f(
+            t + Self::C[1] * h,
+            state.offset(&stage(h, Self::A3, [k1, k2])),
+        );
+        let k4 = f(
+            t + Self::C[2] * h,
+            state.offset(&stage(h, Self::A4, [k1, k2, k3])),
+        );
+        let k5 = f(
+            t + Self::C[3] * h,
+            state.offset(&stage(h, Self::A5, [k1, k2, k3, k4])),
+        );
+        let k6 = f(
+            t + Self::C[4] * h,
+            state.offset(&stage(h, Self::A6, [k1, k2, k3, k4, k5])),
+        );
+        let update = stage(h, Self::B, [k1, k2, k3, k4, k5, k6]);
+        let y_new = state.offset(&update);
+        let k7 = f(t + h, y_new);
+
+        let mut estimate = Accumulator::<T>::new();
+        for (&e, k) in Self::E.iter().zip([k1, k2, k3, k4, k5, k6, k7]) {
+            estimate.add_scaled(k, e * h);
+        }
+        let error = self.error_ratio(&estimate.resolve(), &y, &y_new);
+
+        let factor = if error == 0.0 {
+            Self::MAX_FACTOR
+        } else if !error.is_finite() {
+            Self::MIN_FACTOR
+        } else {
+            (Self::SAFETY / fifth_root(error)).clamp(Self::MIN_FACTOR, Self::MAX_FACTOR)
+        };
+        let h_next = (h * factor).clamp(-self.h_max, self.h_max);
+
+        let accepted = error <= 1.0;
+        if accepted {
+            state.advance(h, &update);
+            state.derivative = Some(k7);
+        } else {
+            state.derivative = Some(k1);
+        }
+        StepReport {
+            accepted,
+            h,
+            error,
+            h_next,
+        }
+    }
+
+    /// Integrate until `t_end`, starting with step size `h`.
+    ///
+    /// Integrates backward in time when `t_end` is before the current time;
+    /// only the magnitude of `h` is used, capped at [`h_max`](Self::h_max),
+    /// and the direction follows `t_end`.
+    /// The last step is shortened to land on `t_end` exactly. Returns the
+    /// (signed) step size the controller would try next, or `None` if the
+    /// step size fell below [`h_min`](Self::h_min) or to zero, or `t_end`
+    /// is NaN. The state is then left at the last accepted step.
+    pub fn integrate_to<T, F>(
+        &self,
+        state: &mut OdeState<T>,
+        t_end: f64,
+        mut h: f64,
+        mut f: F,
+    ) -> Option<f64>
+    where
+        T: OdeVector,
+        F: FnMut(f64, T) -> T,
+    {
+        if t_end.is_nan() {
+            return None;
+        }
+        let direction = if t_end < state.t() { -1.0 } else { 1.0 };
+        // Not `f64::min`, which would turn a NaN `h` into `h_max`.
+        if h.abs() > self.h_max {
+            h = self.h_max;
+        }
+        h = h.abs() * direction;
+        loop {
+            // The distance still to go, positive until `t_end` is reached.
+            let remaining = (t_end - state.t()) * direction;
+            if remaining <= 0.0 {
+                return Some(h);
+            }
+            // Written to fail for a NaN `h` too.
+            if !(h.abs() >= self.h_min && h != 0.0) {
+                return None;
+            }
+            let last = h.abs() >= remaining;
+            let report = self.step(state, if last { remaining * direction } else { h }, &mut f);
+            if report.accepted && last {
+                return Some(h);
+            }
+            h = report.h_next;
+        }
+    }
+
+    /// `max_i |errᵢ| / (atol + rtol · max(|yᵢ|, |y_newᵢ|))`, or NaN if any
+    /// term is NaN (`f64::max` would drop it and accept the step).
+    fn error_ratio<T: OdeVector>(&self, error: &T, y: &T, y_new: &T) -> f64 {
+        (0..T::DIM)
+            .map(|i| {
+                let scale = y.component(i).abs().max(y_new.component(i).abs());
+                error.component(i).abs() / (self.atol + self.rtol * scale)
+            })
+            .fold(0.0, |max, e| if e > max || e.is_nan() { e } else { max })
+    }
+}
+
+/// The stage terms `(aᵢ · h, kᵢ)`, on the stack.
+#[inline]
+fn stage<T: Copy, const N: usize>(h: f64, a: [f64; N], k: [T; N]) -> [(f64, T); N] {
+    std::array::from_fn(|i| (a[i] * h, k[i]))
+}
+
+/// `x^(1/5)` for positive finite `x`, using only `+ - * /`.
+///
+/// A bit-level estimate (within about 10%) refined by Newton's method for a
+/// fixed number of iterations, so the result is identical on every
+/// platform, unlike `powf`. Subnormal `x` is first scaled by `2^100`, since
+/// the bit-level estimate is only that close for normal numbers.
+fn fifth_root(x: f64) -> f64 {
+    const ONE: u64 = 0x3ff0_0000_0000_0000;
+    if x < f64::MIN_POSITIVE {
+        // Both scalings are exact powers of two: (x · 2^100)^(1/5) = x^(1/5) · 2^20.
+        return fifth_root(x * 2f64.powi(100)) * 2f64.powi(-20);
+    }
+    let bits = x.to_bits() as i64 - ONE as i64;
+    let mut r = f64::from_bits((bits / 5 + ONE as i64) as u64);
+    for _ in 0..6 {
+        let r2 = r * r;
+        r = (4.0 * r + x / (r2 * r2)) / 5.0;
+    }
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fifth_root_is_accurate() {
+        for x in [1e-300, 1e-20, 0.003, 0.5, 1.0, 7.0, 32.0, 1e15, 1e300] {
+            let r = fifth_root(x);
+            // r⁵ is within a few ulps of x, so r is within one or two.
+            let r5 = r * r * r * r * r;
+            assert!((r5 / x - 1.0).abs() <= 8.0 * f64::EPSILON, "{x}");
+        }
+        assert_eq!(fifth_root(32.0), 2.0);
+
+        // Subnormals: compare (r · 2^20)⁵ with x · 2^100, both normal.
+        for x in [5e-324, 1e-315, 2.2e-308] {
+            let r = fifth_root(x) * 2f64.powi(20);
+            let r5 = r * r * r * r * r;
+            let x = x * 2f64.powi(100);
+            assert!((r5 / x - 1.0).abs() <= 8.0 * f64::EPSILON, "{x}");
+        }
+    }
+
+    #[test]
+    fn rk4_is_fourth_order() {
+        let error = |steps: usize| {
+            let mut state = OdeState::new(0.0, 1.0);
+            let h = 1.0 / steps as f64;
+            for _ in 0..steps {
+                Rk4.step(&mut state, h, |t, y| y * t.cos());
+            }
+            (state.y() - 1f64.sin().exp()).abs()
+        };
+        let ratio = error(20) / error(40);
+        assert!((14.0..18.0).contains(&ratio), "{ratio}");
+    }
+
+    #[test]
+    fn dormand_prince_meets_tolerance_and_reports_rejections() {
+        // Harmonic oscillator as a VecN state [x, v].
+        let oscillator = |_t: f64, s: VecN<2>| VecN::new([s[1], -s[0]]);
+        let solver = DormandPrince::new(1e-9, 1e-12).unwrap();
+
+        // An oversized first step must be rejected and shrunk.
+        let mut state = OdeState::new(0.0, VecN::new([1.0, 0.0]));
+        let report = solver.step(&mut state, 3.0, oscillator);
+        assert!(!report.accepted && report.error > 1.0);
+        assert!(report.h_next < 3.0);
+        assert_eq!(state.t(), 0.0);
+
+        let t_end = 10.0 * std::f64::consts::TAU;
+        solver
+            .integrate_to(&mut state, t_end, report.h_next, oscillator)
+            .unwrap();
+        assert_eq!(state.t(), t_end);
+        assert!((state.y()[0] - 1.0).abs() < 1e-7, "{:?}", state.y());
+        assert!(state.y()[1].abs() < 1e-7, "{:?}", state.y());
+    }
+
+    #[test]
+    fn dormand_prince_is_deterministic() {
+        // Only correctly rounded operations in `f`, so the pinned bits below
+        // hold on every platform (`sin` would not be).
+        let f = |t: f64, y: Vec3| Vec3::new(y.y, -y.x - 0.1 * y.y, t / (1.0 + y.z * y.z));
+        let solver = DormandPrince::new(1e-8, 1e-10).unwrap();
+        let mut state = OdeState::new(0.0, Vec3::new(1.0, 2.0, 3.0));
+        let mut reports = Vec::new();
+        let mut h = 0.1;
+        while state.t() < 5.0 {
+            let report = solver.step(&mut state, h, f);
+            h = report.h_next;
+            reports.push(report);
+        }
+        let y = state.y();
+        assert_eq!(
+            [y.x, y.y, y.z].map(f64::to_bits),
+            [
+                0xbff4_06dc_f31c_10cc,
+                0x3ff5_142b_4e07_6a7d,
+                0x400f_baf0_54da_7f01
+            ]
+        );
+        assert_eq!(state.t().to_bits(), 0x4014_35e8_61a3_db69);
+        assert_eq!(reports.len(), 71);
+        assert_eq!(reports.iter().filter(|r| !r.accepted).count(), 5);
+        assert!(reports
+            .iter()
+            .all(|r| r.error.is_finite() && r.h_next > 0.0));
+    }
+
+    #[test]
+    fn dormand_prince_validates_parameters() {
+        assert!(DormandPrince::new(0.0, 1e-12).is_some());
+        for (rtol, atol) in [
+            (0.0, 0.0),
+            (1e-9, 0.0),
+            (-1e-9, 1e-12),
+            (1e-9, f64::NAN),
+            (f64::INFINITY, 1e-12),
+        ] {
+            assert_eq!(DormandPrince::new(rtol, atol), None, "{rtol} {atol}");
+        }
+
+        let solver = DormandPrince::new(1e-9, 1e-12).unwrap();
+        assert!(solver.with_step_limits(0.0, f64::INFINITY).is_some());
+        assert!(solver.with_step_limits(0.1, 0.1).is_some());
+        for (h_min, h_max) in [
+            (0.0, 0.0),
+            (0.0, -1.0),
+            (0.0, f64::NAN),
+            (f64::NAN, 1.0),
+            (-1.0, 1.0),
+            (0.2, 0.1),
+        ] {
+            assert_eq!(
+                solver.with_step_limits(h_min, h_max),
+                None,
+                "{h_min} {h_max}"
+            );
+        }
+    }
+
+    #[test]
+    fn dormand_prince_caps_initial_step_at_h_max() {
+        let solver = DormandPrince::new(1e-6, 1e-9)
+            .unwrap()
+            .with_step_limits(0.0, 0.25)
+            .unwrap();
+        let mut state = OdeState::new(0.0, 1.0);
+        let mut steps = Vec::new();
+        solver.integrate_to(&mut state, 1.0, 10.0, |t, y| {
+            steps.push(t);
+            -y
+        });
+        // The first step's stages span at most h_max.
+        assert!(steps[..7].iter().all(|&t| t <= 0.25), "{steps:?}");
+        assert_eq!(state.t(), 1.0);
+    }
+
+    #[test]
+    fn dormand_prince_reevaluates_invalidated_derivative() {
+        let solver = DormandPrince::new(1e-9, 1e-12).unwrap();
+        let mut state = OdeState::new(0.0, 1.0);
+        solver.step(&mut state, 0.01, |_, y| -y);
+
+        // Switch to y' = 0: with the stale k1 = -y the state would move.
+        let y = state.y();
+        state.invalidate_derivative();
+        let report = solver.step(&mut state, 0.01, |_, _| 0.0);
+        assert!(report.accepted);
+        assert_eq!(state.y(), y);
+    }
+
+    #[test]
+    fn dormand_prince_gives_up_below_h_min() {
+        let solver = DormandPrince::new(1e-12, 1e-14)
+            .unwrap()
+            .with_step_limits(1e-3, f64::INFINITY)
+            .unwrap();
+        // y' = y², y(0) = 1 blows up at t = 1.
+        let mut state = OdeState::new(0.0, 1.0);
+        assert_eq!(
+            solver.integrate_to(&mut state, 2.0, 0.1, |_, y| y * y),
+            None
+        );
+        assert!(state.t() < 1.0);
+    }
+
+    #[test]
+    fn dormand_prince_rejects_non_finite_errors() {
+        let solver = DormandPrince::new(1e-9, 1e-12).unwrap();
+        let poisoned = |t: f64, y: f64| if t > 0.05 { f64::NAN } else { -y };
+
+        let mut state = OdeState::new(0.0, 1.0);
+        let report = solver.step(&mut state, 0.1, poisoned);
+        assert!(!report.accepted && report.error.is_nan());
+        assert_eq!(report.h_next, 0.1 * DormandPrince::MIN_FACTOR);
+        assert_eq!((state.t(), state.y()), (0.0, 1.0));
+
+        // With h_min = 0 the step shrinks until it underflows, then gives up.
+        let mut state = OdeState::new(0.0, 1.0);
+        assert_eq!(
+            solver.integrate_to(&mut state, 1.0, 0.1, |_, _| f64::NAN),
+            None
+        );
+        assert_eq!((state.t(), state.y()), (0.0, 1.0));
+    }
+
+    #[test]
+    fn dormand_prince_integrates_backward() {
+        let solver = DormandPrince::new(1e-12, 1e-14).unwrap();
+        let mut state = OdeState::new(1.0, (-1.0f64).exp());
+        let h = solver
+            .integrate_to(&mut state, 0.0, 0.1, |_, y| -y)
+            .unwrap();
+        assert!(h < 0.0);
+        assert_eq!(state.t(), 0.0);
+        assert!((state.y() - 1.0).abs() < 1e-11, "{}", state.y());
+
+        // And forward again with the negative step size it returned.
+        solver.integrate_to(&mut state, 1.0, h, |_, y| -y).unwrap();
+        assert_eq!(state.t(), 1.0);
+        assert!((state.y() - (-1.0f64).exp()).abs() < 1e-12);
+    }
+}
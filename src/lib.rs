@@ -1,8 +1,9 @@
 //! Drift-Linalg — Drift-Free Linear Algebra Primitives
 //!
-//! Provides spatial accumulator types built on top of `drift-kernel`'s
-//! Neumaier-compensated summation. These types allow physics simulations
-//! to maintain bounded numerical error across millions of operations.
+//! Provides spatial accumulator types built on `drift-kernel`'s
+//! Neumaier-compensated summation, carried in [`summation`] with its state
+//! exposed. These types allow physics simulations to maintain bounded
+//! numerical error across millions of operations.
 //!
 //! # Usage
 //!
@@ -23,134 +24,99 @@
 //! # Serialization
 //!
 //! The `serialization` feature enables serde support. Without it, the crate
-//! has zero dependencies beyond `drift-kernel`.
+//! has zero runtime dependencies.
 
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Serialize};
 
-use drift_kernel::Neumaier;
-
-/// A standard 3D vector
+mod accumulate;
+mod dd;
+mod eft;
+mod fixed;
+pub mod integrate;
+mod macros;
+mod mat3;
+pub mod nbody;
+mod quat;
+mod rigid_body;
+mod scalar;
+pub mod summation;
+mod vec2;
+mod vec4;
+mod vecn;
+mod world;
+
+use summation::{Exact, Neumaier, Reproducible, Summation};
+
+pub use accumulate::{Accumulator, CompensatedAccumulate};
+pub use dd::{DoubleDouble, Vec3DD};
+pub use fixed::{Fixed, FixedVec3};
+pub use mat3::Mat3;
+pub use quat::{Quat, QuatAccumulator};
+pub use rigid_body::RigidBody;
+pub use scalar::Scalar;
+pub use vec2::{Vec2, Vec2Accumulator, Vec2AccumulatorF32, Vec2F32, Vector2};
+pub use vec4::{Vec4, Vec4Accumulator, Vec4AccumulatorF32, Vec4F32, Vector4};
+pub use vecn::{AccumulatorN, VecN};
+pub use world::{rebase, WorldPosition, WorldPositionAccumulator, SECTOR_SIZE};
+
+/// A 3D vector generic over its [`Scalar`] component type
 ///
-/// This type is used for inputs and outputs. For accumulation across
-/// many operations, use [`Vec3Accumulator`] instead.
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// Most code uses the [`Vec3`] (f64) or [`Vec3F32`] aliases. This type is
+/// used for inputs and outputs. For accumulation across many operations,
+/// use [`Vec3Accumulator`] instead.
+#[derive(Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
-pub struct Vec3 {
-    pub x: f64,
-    pub y: f64,
-    pub z: f64,
+pub struct Vector3<T: Scalar = f64> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
 }
 
-impl Vec3 {
-    /// The zero vector.
-    pub const ZERO: Self = Self { x: 0.0, y: 0.0, z: 0.0 };
+/// A standard 3D vector with f64 components
+pub type Vec3 = Vector3<f64>;
 
-    /// Create a new Vec3.
-    #[inline]
-    pub const fn new(x: f64, y: f64, z: f64) -> Self {
-        Self { x, y, z }
-    }
+/// A 3D vector with f32 components, e.g. for GPU-facing transforms
+pub type Vec3F32 = Vector3<f32>;
 
-    /// Returns the raw IEEE-754 little-endian bytes.
-    ///
-    /// This is the **only valid way** to hash state for determinism verification.
-    /// Do NOT use text formatting (Debug, Display) for hashing—floating-point
-    /// text representation is not guaranteed to be platform-consistent.
+impl<T: Scalar> Vector3<T> {
+    /// Compute the cross product with another vector.
     #[inline]
-    pub fn to_le_bytes(&self) -> [u8; 24] {
-        let mut buf = [0u8; 24];
-        buf[0..8].copy_from_slice(&self.x.to_le_bytes());
-        buf[8..16].copy_from_slice(&self.y.to_le_bytes());
-        buf[16..24].copy_from_slice(&self.z.to_le_bytes());
-        buf
-    }
-
-    /// Reconstruct a Vec3 from little-endian bytes.
-    ///
-    /// This is the inverse of [`to_le_bytes`](Self::to_le_bytes) and is required
-    /// for checkpoint restore and replay branching.
-    #[inline]
-    pub fn from_le_bytes(bytes: [u8; 24]) -> Self {
+    pub fn cross(&self, other: Self) -> Self {
         Self {
-            x: f64::from_le_bytes(bytes[0..8].try_into().unwrap()),
-            y: f64::from_le_bytes(bytes[8..16].try_into().unwrap()),
-            z: f64::from_le_bytes(bytes[16..24].try_into().unwrap()),
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
         }
     }
 
-    /// Compute the dot product with another vector.
-    #[inline]
-    pub fn dot(&self, other: Vec3) -> f64 {
-        self.x * other.x + self.y * other.y + self.z * other.z
-    }
-
-    /// Compute the squared magnitude (avoids sqrt).
-    #[inline]
-    pub fn magnitude_squared(&self) -> f64 {
-        self.dot(*self)
-    }
-
-    /// Compute the magnitude.
-    #[inline]
-    pub fn magnitude(&self) -> f64 {
-        self.magnitude_squared().sqrt()
-    }
-
-    /// Scale by a scalar.
+    /// Compute the cross product with compensated arithmetic.
+    ///
+    /// Each component is an `ad - bc` term evaluated with Kahan's
+    /// difference-of-products algorithm, which stays accurate to within
+    /// 1.5 ulp even when the operands are nearly parallel and the two
+    /// products cancel.
     #[inline]
-    pub fn scale(&self, scalar: f64) -> Self {
-        Self {
-            x: self.x * scalar,
-            y: self.y * scalar,
-            z: self.z * scalar,
-        }
-    }
-}
-
-impl Default for Vec3 {
-    fn default() -> Self {
-        Self::ZERO
-    }
-}
-
-impl std::ops::Add for Vec3 {
-    type Output = Self;
-    fn add(self, rhs: Self) -> Self {
+    pub fn cross_compensated(&self, other: Self) -> Self {
         Self {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-            z: self.z + rhs.z,
+            x: eft::diff_of_products(self.y, other.z, self.z, other.y),
+            y: eft::diff_of_products(self.z, other.x, self.x, other.z),
+            z: eft::diff_of_products(self.x, other.y, self.y, other.x),
         }
     }
 }
 
-impl std::ops::Sub for Vec3 {
-    type Output = Self;
-    fn sub(self, rhs: Self) -> Self {
-        Self {
-            x: self.x - rhs.x,
-            y: self.y - rhs.y,
-            z: self.z - rhs.z,
-        }
-    }
-}
-
-impl std::ops::Neg for Vec3 {
-    type Output = Self;
-    fn neg(self) -> Self {
-        Self {
-            x: -self.x,
-            y: -self.y,
-            z: -self.z,
-        }
-    }
+macros::vector_impls! {
+    Vector3, Vec3, Vec3F32, Vec3Accumulator,
+    { x: 0, y: 1, z: 2 },
+    len: 3,
+    bytes: (24, 12)
 }
 
 /// A 3D spatial accumulator
 ///
-/// Uses Neumaier-compensated summation on each component to maintain
-/// O(ε) bounded error regardless of operation count.
+/// Uses compensated summation (Neumaier by default) on each component to
+/// maintain O(ε) bounded error regardless of operation count.
 ///
 /// # Example
 ///
@@ -168,86 +134,114 @@ impl std::ops::Neg for Vec3 {
 /// let result = acc.resolve();
 /// assert!(result.x.abs() < 1e-10);
 /// ```
+///
+/// # Serialization
+///
+/// With the `serialization` feature, the full sum-and-compensation pair of
+/// each axis is serialized, so restoring a checkpoint continues bit-exactly
+/// as if the run had never been interrupted.
+///
+/// # Summation Strategy
+///
+/// The per-axis algorithm is a type parameter defaulting to [`Neumaier`].
+/// Use `Vec3Accumulator<Kahan>` for cheap particles or
+/// `Vec3Accumulator<Klein>` for long-horizon runs. Accumulation, merging
+/// and resolving are the same for every strategy.
+///
+/// Construction is not: as with `HashMap::new`, [`new`](Self::new) and
+/// [`with_initial`](Self::with_initial) exist only on the default
+/// `Vec3Accumulator`, so that `Vec3Accumulator::new()` needs no type
+/// annotation. Other strategies, and generic code, use `default()` and
+/// [`from_initial`](Self::from_initial). The byte checkpoints
+/// ([`to_le_bytes`](Self::to_le_bytes)) are also specific to the default
+/// Neumaier layout.
+///
+/// The strategy also fixes the component type: `Vec3Accumulator<Neumaier<f32>>`
+/// (aliased as [`Vec3AccumulatorF32`]) accumulates [`Vec3F32`] values.
+///
+/// ```rust
+/// use drift_linalg::summation::Klein;
+/// use drift_linalg::{Vec3, Vec3Accumulator};
+///
+/// let mut orbit = Vec3Accumulator::<Klein>::default();
+/// orbit.add(Vec3::new(1.0, 2.0, 3.0));
+/// assert_eq!(orbit.resolve(), Vec3::new(1.0, 2.0, 3.0));
+/// ```
 #[derive(Debug, Clone)]
-pub struct Vec3Accumulator {
-    x: Neumaier,
-    y: Neumaier,
-    z: Neumaier,
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct Vec3Accumulator<S: Summation = Neumaier> {
+    x: S,
+    y: S,
+    z: S,
 }
 
 impl Vec3Accumulator {
-    /// Create a new zero-initialized accumulator.
+    /// The per-axis running sums, for conversions that keep compensation.
     #[inline]
-    pub fn new() -> Self {
-        Self::default()
-    }
-
-    /// Create an accumulator with an initial value.
-    #[inline]
-    pub fn with_initial(initial: Vec3) -> Self {
-        Self {
-            x: Neumaier::new(initial.x),
-            y: Neumaier::new(initial.y),
-            z: Neumaier::new(initial.z),
-        }
-    }
-
-    /// Add a vector to the accumulator.
-    #[inline]
-    pub fn add(&mut self, vec: Vec3) {
-        self.x.add(vec.x);
-        self.y.add(vec.y);
-        self.z.add(vec.z);
+    pub(crate) fn components(&self) -> (&Neumaier, &Neumaier, &Neumaier) {
+        (&self.x, &self.y, &self.z)
     }
+}
 
-    /// Add a scaled vector to the accumulator.
-    ///
-    /// # Note on Compensation
-    ///
-    /// **The scalar multiplication is NOT compensated.** Only the accumulation
-    /// into the internal state uses Neumaier summation. The multiplication
-    /// `vec.x * scalar` happens in standard f64 arithmetic.
-    ///
-    /// This is standard practice in numerical integration and is acceptable
-    /// for most physics simulations. If you require compensated multiplication,
-    /// you must implement it externally.
-    #[inline]
-    pub fn add_scaled(&mut self, vec: Vec3, scalar: f64) {
-        self.x.add(vec.x * scalar);
-        self.y.add(vec.y * scalar);
-        self.z.add(vec.z * scalar);
-    }
+macros::accumulator_impls! {
+    Vec3Accumulator, Vector3, Vec3,
+    { x, y, z },
+    bytes: 48
+}
 
-    /// Resolve the accumulator to a standard Vec3.
-    ///
-    /// This extracts the compensated total from each component.
-    #[inline]
-    pub fn resolve(&self) -> Vec3 {
-        Vec3 {
-            x: self.x.total(),
-            y: self.y.total(),
-            z: self.z.total(),
-        }
-    }
+/// A [`Vec3Accumulator`] whose result is the correctly rounded exact sum
+///
+/// Each axis is an [`Exact`] superaccumulator, so [`resolve`](Vec3Accumulator::resolve)
+/// returns the same bits for any input order, and that result is the
+/// reference against which the bounded-error accumulators can be checked.
+/// It is considerably slower and larger than the default accumulator.
+///
+/// # Example
+///
+/// ```rust
+/// use drift_linalg::{ExactVec3Accumulator, Vec3};
+///
+/// let mut reference = ExactVec3Accumulator::default();
+/// reference.add(Vec3::new(1.0, 1e100, 1.0));
+/// reference.add(Vec3::new(1e100, 1.0, -1.0));
+/// reference.add(Vec3::new(-1e100, -1e100, 1.0));
+/// assert_eq!(reference.resolve(), Vec3::new(1.0, 1.0, 1.0));
+/// ```
+pub type ExactVec3Accumulator = Vec3Accumulator<Exact>;
 
-    /// Reset the accumulator to zero.
-    #[inline]
-    pub fn reset(&mut self) {
-        self.x.reset();
-        self.y.reset();
-        self.z.reset();
-    }
-}
+/// A [`Vec3Accumulator`] whose result does not depend on input order
+///
+/// Each axis is a [`Reproducible`] binned sum, so adding the same vectors in
+/// any order, or merging partial accumulators in any tree shape, resolves
+/// to identical bits. Use it for multithreaded reductions whose scheduling
+/// is not fixed.
+pub type ReproducibleVec3Accumulator = Vec3Accumulator<Reproducible>;
 
-impl Default for Vec3Accumulator {
-    fn default() -> Self {
-        Self {
-            x: Neumaier::new(0.0),
-            y: Neumaier::new(0.0),
-            z: Neumaier::new(0.0),
-        }
-    }
-}
+/// A [`Vec3Accumulator`] over f32 components
+///
+/// Plain f32 accumulation drifts after only a few thousand frames, and this
+/// is where compensation pays off most. Over very long f32 runs the
+/// first-order compensation term itself starts to round; use
+/// `Vec3Accumulator<Klein<f32>>` there.
+///
+/// # Example
+///
+/// ```rust
+/// use drift_linalg::{Vec3AccumulatorF32, Vec3F32};
+///
+/// let mut position = Vec3AccumulatorF32::default();
+/// let mut naive = Vec3F32::ZERO;
+/// let step = Vec3F32::new(0.1, 0.01, 0.001);
+/// for _ in 0..100_000 {
+///     position.add(step);
+///     naive = naive + step;
+/// }
+///
+/// // 100k steps of 0.1: the naive sum is off by more than a whole unit.
+/// assert!((position.resolve().x - 10_000.0).abs() < 0.01);
+/// assert!((naive.x - 10_000.0).abs() > 1.0);
+/// ```
+pub type Vec3AccumulatorF32 = Vec3Accumulator<Neumaier<f32>>;
 
 #[cfg(test)]
 mod tests {
@@ -261,6 +255,77 @@ mod tests {
         assert_eq!(original, restored);
     }
 
+    #[test]
+    fn debug_uses_alias_names() {
+        let v = Vec3::new(1.0, -2.0, 0.5);
+        assert_eq!(format!("{v:?}"), "Vec3 { x: 1.0, y: -2.0, z: 0.5 }");
+        let w = Vec3F32::new(1.0, -2.0, 0.5);
+        assert_eq!(format!("{w:?}"), "Vec3F32 { x: 1.0, y: -2.0, z: 0.5 }");
+        let u = Vec2F32::new(1.0, 0.5);
+        assert_eq!(format!("{u:?}"), "Vec2F32 { x: 1.0, y: 0.5 }");
+        assert_eq!(
+            format!("{:?}", Vec4::new(1.0, 0.0, 0.0, 2.0)),
+            "Vec4 { x: 1.0, y: 0.0, z: 0.0, w: 2.0 }"
+        );
+    }
+
+    #[test]
+    fn sum_compensated_for_every_dimension() {
+        let big = 1e16;
+        let x = |x| Vec2::new(x, 1.0);
+        let sum = Vec2::sum_compensated([x(big), x(1.0), x(-big)]);
+        assert_eq!(sum, Vec2::new(1.0, 3.0));
+        let w = |w| Vec4::new(0.0, 0.0, 0.0, w);
+        assert_eq!(Vec4::sum_compensated([w(big), w(1.0), w(-big)]), w(1.0));
+    }
+
+    #[test]
+    fn vec3_dot_compensated_cancellation() {
+        // Naive evaluation loses the small term entirely: 1e16 + 1 - 1e16 == 0.
+        let a = Vec3::new(1e8, 1.0, -1e8);
+        let b = Vec3::new(1e8, 1.0, 1e8);
+        assert_eq!(a.dot(b), 0.0);
+        assert_eq!(a.dot_compensated(b), 1.0);
+    }
+
+    #[test]
+    fn vec3_dot_compensated_captures_product_error() {
+        // (1 + 2^-30)^2 = 1 + 2^-29 + 2^-60; the last term is below f64 precision
+        // of the product but survives cancellation against -(1 + 2^-29).
+        let e = 2f64.powi(-30);
+        let a = Vec3::new(1.0 + e, 1.0, 0.0);
+        let b = Vec3::new(1.0 + e, -(1.0 + 2.0 * e), 0.0);
+        assert_eq!(a.dot(b), 0.0);
+        assert_eq!(a.dot_compensated(b), e * e);
+    }
+
+    #[test]
+    fn vec3_magnitude_compensated() {
+        let v = Vec3::new(3.0, 4.0, 12.0);
+        assert_eq!(v.magnitude_squared_compensated(), 169.0);
+        assert_eq!(v.magnitude_compensated(), 13.0);
+    }
+
+    #[test]
+    fn vec3_cross_basis() {
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let y = Vec3::new(0.0, 1.0, 0.0);
+        assert_eq!(x.cross(y), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(y.cross(x), Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(x.cross_compensated(y), Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn vec3_cross_compensated_near_parallel() {
+        // r and p differ only in the last bits; the true z component is
+        // (1 + e)(1 + e) - 1 * (1 + 2e) = e^2, which naive evaluation rounds away.
+        let e = 2f64.powi(-30);
+        let r = Vec3::new(1.0 + e, 1.0 + 2.0 * e, 0.0);
+        let p = Vec3::new(1.0, 1.0 + e, 0.0);
+        assert_eq!(r.cross(p).z, 0.0);
+        assert_eq!(r.cross_compensated(p), Vec3::new(0.0, 0.0, e * e));
+    }
+
     #[test]
     fn vec3_accumulator_basic() {
         let mut acc = Vec3Accumulator::new();
@@ -314,4 +379,251 @@ mod tests {
         assert!((result.y - 10.0).abs() < 1e-15);
         assert!((result.z - 15.0).abs() < 1e-15);
     }
+
+    #[test]
+    fn vec3_accumulator_add_scaled_exact_keeps_product_error() {
+        // (1 + 2^-30)^2 = 1 + 2^-29 + 2^-60; the 2^-60 is lost by a rounded product.
+        let e = 2f64.powi(-30);
+        let vec = Vec3::new(1.0 + e, 1.0 + e, 1.0 + e);
+        let rounded = Vec3::new(1.0 + 2.0 * e, 1.0 + 2.0 * e, 1.0 + 2.0 * e);
+
+        let mut naive = Vec3Accumulator::new();
+        naive.add_scaled(vec, 1.0 + e);
+        naive.add(-rounded);
+        assert_eq!(naive.resolve(), Vec3::ZERO);
+
+        let mut exact = Vec3Accumulator::new();
+        exact.add_scaled_exact(vec, 1.0 + e);
+        exact.add(-rounded);
+        assert_eq!(exact.resolve(), Vec3::new(e * e, e * e, e * e));
+    }
+
+    #[test]
+    fn vec3_accumulator_to_from_le_bytes_roundtrip() {
+        let mut acc = Vec3Accumulator::new();
+        acc.add(Vec3::new(1e16, -1e16, 0.5));
+        acc.add(Vec3::new(1.0, -1.0, 0.25));
+
+        let bytes = acc.to_le_bytes();
+        assert_eq!(bytes[0..8], 1e16f64.to_le_bytes());
+        assert_eq!(bytes[8..16], 1.0f64.to_le_bytes());
+
+        let mut restored = Vec3Accumulator::from_le_bytes(bytes);
+        assert_eq!(restored.to_le_bytes(), bytes);
+
+        acc.add(Vec3::new(-1e16, 1e16, 0.0));
+        restored.add(Vec3::new(-1e16, 1e16, 0.0));
+        assert_eq!(restored.resolve(), Vec3::new(1.0, -1.0, 0.75));
+        assert_eq!(restored.to_le_bytes(), acc.to_le_bytes());
+    }
+
+    #[test]
+    fn vec3_accumulator_merge_keeps_compensation() {
+        let mut a = Vec3Accumulator::new();
+        a.add(Vec3::new(1e16, 1e16, 1e16));
+        a.add(Vec3::new(1.0, 1.0, 1.0));
+        let mut b = Vec3Accumulator::new();
+        b.add(Vec3::new(-1e16, -1e16, -1e16));
+        b.add(Vec3::new(1.0, 1.0, 1.0));
+
+        a.merge(&b);
+        assert_eq!(a.resolve(), Vec3::new(2.0, 2.0, 2.0));
+    }
+
+    #[test]
+    fn vec3_accumulator_tree_reduce_matches_pairwise_merge() {
+        let values: Vec<Vec3> = (0..1000)
+            .map(|i| {
+                let s = if i % 2 == 0 { 1e15 } else { -1e15 };
+                Vec3::new(s + i as f64 * 0.1, 1e-3 * i as f64, -s)
+            })
+            .collect();
+
+        let parts: Vec<Vec3Accumulator> = values
+            .chunks(64)
+            .map(|chunk| {
+                let mut acc = Vec3Accumulator::new();
+                chunk.iter().for_each(|v| acc.add(*v));
+                acc
+            })
+            .collect();
+        let reduced = Vec3Accumulator::tree_reduce(&parts);
+
+        // 16 parts: merging adjacent pairs level by level visits the same tree.
+        assert_eq!(parts.len(), 16);
+        let mut level = parts.clone();
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| {
+                    let mut acc = pair[0].clone();
+                    acc.merge(&pair[1]);
+                    acc
+                })
+                .collect();
+        }
+        assert_eq!(reduced.to_le_bytes(), level[0].to_le_bytes());
+
+        let expected: f64 = (0..1000).map(|i| i as f64 * 0.1).sum();
+        assert!((reduced.resolve().x - expected).abs() < 1e-9);
+        let empty: &[Vec3Accumulator] = &[];
+        assert_eq!(Vec3Accumulator::tree_reduce(empty).resolve(), Vec3::ZERO);
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn vec3_accumulator_serde_preserves_compensation() {
+        let mut acc = Vec3Accumulator::new();
+        acc.add(Vec3::new(1e16, 1e16, 1e16));
+        acc.add(Vec3::new(1.0, 1.0, 1.0));
+
+        let json = serde_json::to_string(&acc).unwrap();
+        let mut restored: Vec3Accumulator = serde_json::from_str(&json).unwrap();
+
+        // Resolving before the cancellation would lose the 1.0.
+        acc.add(Vec3::new(-1e16, -1e16, -1e16));
+        restored.add(Vec3::new(-1e16, -1e16, -1e16));
+        assert_eq!(restored.resolve(), Vec3::new(1.0, 1.0, 1.0));
+        assert_eq!(restored.resolve().to_le_bytes(), acc.resolve().to_le_bytes());
+    }
+
+    #[test]
+    fn vec3_accumulator_within_bound_of_exact() {
+        let mut acc = Vec3Accumulator::new();
+        let mut exact = ExactVec3Accumulator::default();
+        let velocity = Vec3::new(0.1, -1e-7, 12345.678);
+        for i in 0..100_000 {
+            let v = velocity.scale(1.0 + (i % 7) as f64 * 1e-3);
+            acc.add_scaled(v, 1.0 / 60.0);
+            exact.add_scaled(v, 1.0 / 60.0);
+        }
+        let (r, e) = (acc.resolve(), exact.resolve());
+        for (got, want) in [(r.x, e.x), (r.y, e.y), (r.z, e.z)] {
+            assert!((got - want).abs() <= want.abs() * f64::EPSILON, "{got} vs {want}");
+        }
+    }
+
+    #[test]
+    fn vec3_sum_reproducible_permutation_invariant() {
+        let forces: Vec<Vec3> = (0..1000)
+            .map(|i| {
+                let t = i as f64;
+                Vec3::new(1e10 / (t + 1.0), -0.1 * t, (t * 0.37).fract() - 0.5)
+            })
+            .collect();
+        let forward = Vec3::sum_reproducible(&forces);
+        let reversed: Vec<Vec3> = forces.iter().rev().copied().collect();
+        assert_eq!(Vec3::sum_reproducible(&reversed).to_le_bytes(), forward.to_le_bytes());
+
+        let mut parallel = ReproducibleVec3Accumulator::default();
+        for chunk in forces.rchunks(37) {
+            let mut part = ReproducibleVec3Accumulator::default();
+            chunk.iter().for_each(|v| part.add(*v));
+            parallel.merge(&part);
+        }
+        assert_eq!(parallel.resolve().to_le_bytes(), forward.to_le_bytes());
+    }
+
+    #[test]
+    fn vec3_f32_to_from_le_bytes_roundtrip() {
+        let original = Vec3F32::new(1.5, -0.1, f32::MIN_POSITIVE);
+        let bytes = original.to_le_bytes();
+        assert_eq!(bytes.len(), 12);
+        assert_eq!(&bytes[4..8], &(-0.1f32).to_le_bytes());
+        assert_eq!(Vec3F32::from_le_bytes(bytes), original);
+    }
+
+    #[test]
+    fn vec3_accumulator_f32_cancellation() {
+        // 1e8 has an f32 ulp of 8; the small terms survive only in the compensation.
+        let mut acc = Vec3AccumulatorF32::default();
+        acc.add(Vec3F32::new(1e8, -1e8, 1e8));
+        acc.add(Vec3F32::new(1.0, 2.0, 3.0));
+        acc.add(Vec3F32::new(-1e8, 1e8, -1e8));
+        assert_eq!(acc.resolve(), Vec3F32::new(1.0, 2.0, 3.0));
+
+        let mut klein = Vec3Accumulator::<summation::Klein<f32>>::default();
+        let mut exact = ExactVec3Accumulator::default();
+        let step = Vec3F32::new(0.1, 0.01, 0.001);
+        for _ in 0..100_000 {
+            klein.add(step);
+            exact.add(Vec3::new(step.x as f64, step.y as f64, step.z as f64));
+        }
+        assert_eq!(klein.resolve().x, exact.resolve().x as f32);
+    }
+
+    #[test]
+    fn vec3_f32_compensated_dot() {
+        let e = 2f32.powi(-13);
+        let a = Vec3F32::new(1.0 + e, 1.0, 0.0);
+        let b = Vec3F32::new(1.0 + e, -(1.0 + 2.0 * e), 0.0);
+        assert_eq!(a.dot(b), 0.0);
+        assert_eq!(a.dot_compensated(b), e * e);
+    }
+
+    #[test]
+    fn vec3_operators() {
+        let mut v = Vec3::from([1.0, 2.0, 3.0]);
+        assert_eq!(v * 2.0, Vec3::new(2.0, 4.0, 6.0));
+        assert_eq!(2.0 * v, v * 2.0);
+        assert_eq!(v / 3.0, Vec3::new(1.0 / 3.0, 2.0 / 3.0, 1.0));
+
+        v += Vec3::new(1.0, 1.0, 1.0);
+        v -= Vec3::new(0.0, 0.0, 1.0);
+        v *= 0.5;
+        v /= 0.25;
+        assert_eq!(v, Vec3::new(4.0, 6.0, 6.0));
+
+        v[1] = -1.0;
+        assert_eq!(v[0] + v[1] + v[2], 9.0);
+        assert_eq!(<[f64; 3]>::from(v), [4.0, -1.0, 6.0]);
+
+        let vs = [Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 2.0, 0.0)];
+        assert_eq!(vs.iter().sum::<Vec3>(), Vec3::new(1.0, 2.0, 0.0));
+        assert_eq!(vs.into_iter().sum::<Vec3>(), Vec3::new(1.0, 2.0, 0.0));
+        assert_eq!(
+            0.5f32 * Vec3F32::new(2.0, 4.0, 6.0),
+            Vec3F32::new(1.0, 2.0, 3.0)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of range")]
+    fn vec3_index_out_of_range() {
+        let _ = Vec3::ZERO[3];
+    }
+
+    #[test]
+    fn vec3_accumulator_add_assign_is_compensated() {
+        let mut acc = Vec3Accumulator::new();
+        acc += Vec3::new(1e16, 1e16, 1e16);
+        acc += Vec3::new(1.0, 1.0, 1.0);
+        acc += Vec3::new(-1e16, -1e16, -1e16);
+        assert_eq!(acc.resolve(), Vec3::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn vec3_accumulator_iterator_integration() {
+        let forces = vec![
+            Vec3::new(1e16, -1e16, 0.5),
+            Vec3::new(1.0, 1.0, 0.25),
+            Vec3::new(-1e16, 1e16, 0.25),
+        ];
+        let expected = Vec3::new(1.0, 1.0, 1.0);
+
+        let summed: Vec3Accumulator = forces.iter().copied().sum();
+        assert_eq!(summed.resolve(), expected);
+        assert_eq!(forces.iter().sum::<Vec3Accumulator>().resolve(), expected);
+        let collected: Vec3Accumulator = forces.iter().copied().collect();
+        assert_eq!(collected.resolve(), expected);
+        assert_eq!(Vec3::sum_compensated(forces.iter().copied()), expected);
+
+        let mut acc = Vec3Accumulator::with_initial(Vec3::new(1.0, 0.0, 0.0));
+        acc.extend(&forces);
+        acc.extend(forces.iter().map(|f| -*f));
+        assert_eq!(acc.resolve(), Vec3::new(1.0, 0.0, 0.0));
+
+        let exact: ExactVec3Accumulator = forces.iter().sum();
+        assert_eq!(exact.resolve(), expected);
+    }
 }
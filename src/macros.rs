@@ -0,0 +1,584 @@
+//! Shared implementations of the fixed-size vector types.
+//!
+//! [`Vector2`](crate::Vector2), [`Vector3`](crate::Vector3) and
+//! [`Vector4`](crate::Vector4) differ only in their component count, so their
+//! arithmetic, byte layout and accumulators are generated here once. Each
+//! type keeps its struct definition, docs and dimension-specific methods
+//! (e.g. `cross`, `perp_dot`, `xyz`) in its own module.
+
+/// Implements the component-wise vector API for a `Vector{2,3,4}` type.
+///
+/// Generates the generic methods (including `sum_compensated`), `Debug`
+/// named after the public aliases, the f64 and f32 byte conversions,
+/// `sum_reproducible`, and the operator, `Sum` and array conversion impls.
+/// Fields are listed in memory order with their index, followed by the
+/// component count and the f64 and f32 byte lengths.
+macro_rules! vector_impls {
+    (
+        $Vector:ident, $F64:ident, $F32:ident, $Acc:ident,
+        { $first:ident: $first_index:literal $(, $field:ident: $index:literal)* },
+        len: $len:literal,
+        bytes: ($f64_bytes:literal, $f32_bytes:literal)
+    ) => {
+        impl<T: $crate::Scalar> $Vector<T> {
+            /// The zero vector.
+            pub const ZERO: Self = Self {
+                $first: T::ZERO,
+                $($field: T::ZERO,)*
+            };
+
+            /// Create a new vector.
+            #[inline]
+            pub const fn new($first: T, $($field: T),*) -> Self {
+                Self { $first, $($field),* }
+            }
+
+            /// Compute the dot product with another vector.
+            #[inline]
+            pub fn dot(&self, other: Self) -> T {
+                self.$first * other.$first $(+ self.$field * other.$field)*
+            }
+
+            /// Compute the dot product with compensated arithmetic.
+            ///
+            /// Each product is split into its rounded value and exact error
+            /// (TwoProduct), and the partial sums are tracked with TwoSum. The
+            /// result is as accurate as if computed in twice the working
+            /// precision, which matters when projecting onto nearly-orthogonal
+            /// directions.
+            #[inline]
+            pub fn dot_compensated(&self, other: Self) -> T {
+                $crate::eft::dot2(
+                    &[self.$first, $(self.$field),*],
+                    &[other.$first, $(other.$field),*],
+                )
+            }
+
+            /// Compute the squared magnitude (avoids sqrt).
+            #[inline]
+            pub fn magnitude_squared(&self) -> T {
+                self.dot(*self)
+            }
+
+            /// Compute the squared magnitude with compensated arithmetic.
+            ///
+            /// See [`dot_compensated`](Self::dot_compensated).
+            #[inline]
+            pub fn magnitude_squared_compensated(&self) -> T {
+                self.dot_compensated(*self)
+            }
+
+            /// Compute the magnitude.
+            #[inline]
+            pub fn magnitude(&self) -> T {
+                self.magnitude_squared().sqrt()
+            }
+
+            /// Compute the magnitude from the compensated squared magnitude.
+            ///
+            /// The only rounding beyond the compensated sum is the final `sqrt`.
+            #[inline]
+            pub fn magnitude_compensated(&self) -> T {
+                self.magnitude_squared_compensated().sqrt()
+            }
+
+            /// Scale by a scalar.
+            #[inline]
+            pub fn scale(&self, scalar: T) -> Self {
+                Self {
+                    $first: self.$first * scalar,
+                    $($field: self.$field * scalar,)*
+                }
+            }
+
+            /// Sum vectors with Neumaier compensation and resolve the result.
+            ///
+            #[doc = concat!(
+                "Shorthand for collecting into a [`", stringify!($Acc), "`](crate::",
+                stringify!($Acc), ") and calling [`resolve`](crate::",
+                stringify!($Acc), "::resolve).",
+            )]
+            ///
+            /// ```rust
+            #[doc = concat!("use drift_linalg::", stringify!($F64), ";")]
+            ///
+            #[doc = concat!("let mut big = ", stringify!($F64), "::ZERO;")]
+            #[doc = concat!("big.", stringify!($first), " = 1e16;")]
+            #[doc = concat!("let mut one = ", stringify!($F64), "::ZERO;")]
+            #[doc = concat!("one.", stringify!($first), " = 1.0;")]
+            /// let values = [big, one, -big];
+            #[doc = concat!("assert_eq!(", stringify!($F64), "::sum_compensated(values), one);")]
+            ///
+            /// // Plain summation loses the 1.0.
+            #[doc = concat!(
+                "assert_eq!(values.into_iter().sum::<", stringify!($F64), ">(), ",
+                stringify!($F64), "::ZERO);",
+            )]
+            /// ```
+            pub fn sum_compensated<I: IntoIterator<Item = Self>>(values: I) -> Self {
+                values
+                    .into_iter()
+                    .collect::<$Acc<$crate::summation::Neumaier<T>>>()
+                    .resolve()
+            }
+        }
+
+        #[doc = concat!(
+            "Formats as `", stringify!($F64), " { .. }` or `", stringify!($F32),
+            " { .. }`, after the public aliases.",
+        )]
+        impl<T: $crate::Scalar> std::fmt::Debug for $Vector<T> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                // `Scalar` is sealed to f32 and f64, so the size tells them apart.
+                let name = if std::mem::size_of::<T>() == 4 {
+                    stringify!($F32)
+                } else {
+                    stringify!($F64)
+                };
+                f.debug_struct(name)
+                    .field(stringify!($first), &self.$first)
+                    $(.field(stringify!($field), &self.$field))*
+                    .finish()
+            }
+        }
+
+        impl $F64 {
+            #[doc = concat!(
+                "Returns the raw IEEE-754 little-endian bytes (",
+                stringify!($f64_bytes), " bytes, 8 per component).\n\n",
+                "This is the **only valid way** to hash state for determinism ",
+                "verification. Do NOT use text formatting (Debug, Display) for ",
+                "hashing—floating-point text representation is not guaranteed ",
+                "to be platform-consistent.",
+            )]
+            #[inline]
+            pub fn to_le_bytes(&self) -> [u8; $f64_bytes] {
+                let mut buf = [0u8; $f64_bytes];
+                let mut chunks = buf.chunks_exact_mut(8);
+                chunks.next().unwrap().copy_from_slice(&self.$first.to_le_bytes());
+                $(chunks.next().unwrap().copy_from_slice(&self.$field.to_le_bytes());)*
+                buf
+            }
+
+            #[doc = concat!("Reconstruct a ", stringify!($F64), " from little-endian bytes.")]
+            ///
+            /// This is the inverse of [`to_le_bytes`](Self::to_le_bytes) and is
+            /// required for checkpoint restore and replay branching.
+            #[inline]
+            pub fn from_le_bytes(bytes: [u8; $f64_bytes]) -> Self {
+                let mut chunks = bytes.chunks_exact(8);
+                Self {
+                    $first: f64::from_le_bytes(chunks.next().unwrap().try_into().unwrap()),
+                    $($field: f64::from_le_bytes(chunks.next().unwrap().try_into().unwrap()),)*
+                }
+            }
+
+            /// Sum a slice with bit-identical results for any permutation.
+            ///
+            /// Uses [`Reproducible`](crate::summation::Reproducible) binned
+            /// summation per axis: reordering `values` (e.g. because task
+            /// scheduling changed) cannot change a single bit of the result.
+            #[doc = concat!(
+                "For the streaming or parallel form, merge partial `",
+                stringify!($Acc), "<Reproducible>` accumulators.",
+            )]
+            pub fn sum_reproducible(values: &[$F64]) -> $F64 {
+                let mut acc = $Acc::<$crate::summation::Reproducible>::default();
+                values.iter().for_each(|v| acc.add(*v));
+                acc.resolve()
+            }
+        }
+
+        impl $F32 {
+            #[doc = concat!(
+                "Returns the raw IEEE-754 little-endian bytes (",
+                stringify!($f32_bytes), " bytes, 4 per component).\n\n",
+                "See [`", stringify!($F64), "::to_le_bytes`].",
+            )]
+            #[inline]
+            pub fn to_le_bytes(&self) -> [u8; $f32_bytes] {
+                let mut buf = [0u8; $f32_bytes];
+                let mut chunks = buf.chunks_exact_mut(4);
+                chunks.next().unwrap().copy_from_slice(&self.$first.to_le_bytes());
+                $(chunks.next().unwrap().copy_from_slice(&self.$field.to_le_bytes());)*
+                buf
+            }
+
+            #[doc = concat!("Reconstruct a ", stringify!($F32), " from little-endian bytes.")]
+            ///
+            /// This is the inverse of [`to_le_bytes`](Self::to_le_bytes).
+            #[inline]
+            pub fn from_le_bytes(bytes: [u8; $f32_bytes]) -> Self {
+                let mut chunks = bytes.chunks_exact(4);
+                Self {
+                    $first: f32::from_le_bytes(chunks.next().unwrap().try_into().unwrap()),
+                    $($field: f32::from_le_bytes(chunks.next().unwrap().try_into().unwrap()),)*
+                }
+            }
+        }
+
+        impl<T: $crate::Scalar> Default for $Vector<T> {
+            fn default() -> Self {
+                Self::ZERO
+            }
+        }
+
+        impl<T: $crate::Scalar> std::ops::Add for $Vector<T> {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                Self {
+                    $first: self.$first + rhs.$first,
+                    $($field: self.$field + rhs.$field,)*
+                }
+            }
+        }
+
+        impl<T: $crate::Scalar> std::ops::Sub for $Vector<T> {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                Self {
+                    $first: self.$first - rhs.$first,
+                    $($field: self.$field - rhs.$field,)*
+                }
+            }
+        }
+
+        impl<T: $crate::Scalar> std::ops::Neg for $Vector<T> {
+            type Output = Self;
+            fn neg(self) -> Self {
+                Self {
+                    $first: -self.$first,
+                    $($field: -self.$field,)*
+                }
+            }
+        }
+
+        impl<T: $crate::Scalar> std::ops::Mul<T> for $Vector<T> {
+            type Output = Self;
+            fn mul(self, rhs: T) -> Self {
+                self.scale(rhs)
+            }
+        }
+
+        impl std::ops::Mul<$F64> for f64 {
+            type Output = $F64;
+            fn mul(self, rhs: $F64) -> $F64 {
+                rhs.scale(self)
+            }
+        }
+
+        impl std::ops::Mul<$F32> for f32 {
+            type Output = $F32;
+            fn mul(self, rhs: $F32) -> $F32 {
+                rhs.scale(self)
+            }
+        }
+
+        impl<T: $crate::Scalar> std::ops::Div<T> for $Vector<T> {
+            type Output = Self;
+            /// Divides each component, so results match `x / s` exactly
+            /// (unlike scaling by `1 / s`, which rounds twice).
+            fn div(self, rhs: T) -> Self {
+                Self {
+                    $first: self.$first / rhs,
+                    $($field: self.$field / rhs,)*
+                }
+            }
+        }
+
+        impl<T: $crate::Scalar> std::ops::AddAssign for $Vector<T> {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl<T: $crate::Scalar> std::ops::SubAssign for $Vector<T> {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl<T: $crate::Scalar> std::ops::MulAssign<T> for $Vector<T> {
+            fn mul_assign(&mut self, rhs: T) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl<T: $crate::Scalar> std::ops::DivAssign<T> for $Vector<T> {
+            fn div_assign(&mut self, rhs: T) {
+                *self = *self / rhs;
+            }
+        }
+
+        impl<T: $crate::Scalar> std::ops::Index<usize> for $Vector<T> {
+            type Output = T;
+            /// Components in declaration order. Panics if `index` is out of
+            /// range.
+            fn index(&self, index: usize) -> &T {
+                match index {
+                    $first_index => &self.$first,
+                    $($index => &self.$field,)*
+                    _ => panic!(concat!(stringify!($Vector), " index out of range: {}"), index),
+                }
+            }
+        }
+
+        impl<T: $crate::Scalar> std::ops::IndexMut<usize> for $Vector<T> {
+            fn index_mut(&mut self, index: usize) -> &mut T {
+                match index {
+                    $first_index => &mut self.$first,
+                    $($index => &mut self.$field,)*
+                    _ => panic!(concat!(stringify!($Vector), " index out of range: {}"), index),
+                }
+            }
+        }
+
+        /// Plain, uncompensated summation, in iterator order.
+        ///
+        /// For long or ill-conditioned sums, use `sum_compensated` or sum into
+        /// an accumulator instead.
+        impl<T: $crate::Scalar> std::iter::Sum for $Vector<T> {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self::ZERO, |acc, v| acc + v)
+            }
+        }
+
+        impl<'a, T: $crate::Scalar> std::iter::Sum<&'a $Vector<T>> for $Vector<T> {
+            fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                iter.copied().sum()
+            }
+        }
+
+        impl<T: $crate::Scalar> From<[T; $len]> for $Vector<T> {
+            fn from([$first, $($field),*]: [T; $len]) -> Self {
+                Self { $first, $($field),* }
+            }
+        }
+
+        impl<T: $crate::Scalar> From<$Vector<T>> for [T; $len] {
+            fn from(v: $Vector<T>) -> Self {
+                [v.$first, $(v.$field),*]
+            }
+        }
+    };
+}
+
+/// Implements the accumulator API for a `Vec{2,3,4}Accumulator` type.
+///
+/// Generates the default-strategy constructors and byte checkpoints, the
+/// strategy-generic methods, and the `AddAssign`, `Extend`, `FromIterator`
+/// and `Sum` impls. Fields are listed in memory order.
+macro_rules! accumulator_impls {
+    (
+        $Acc:ident, $Vector:ident, $F64:ident,
+        { $($field:ident),+ },
+        bytes: $bytes:literal
+    ) => {
+        impl $Acc {
+            /// Create a new zero-initialized accumulator.
+            #[inline]
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// Create an accumulator with an initial value.
+            #[inline]
+            pub fn with_initial(initial: $F64) -> Self {
+                Self::from_initial(initial)
+            }
+
+            /// Returns the full accumulator state as little-endian bytes.
+            ///
+            /// The layout is the running sum followed by the compensation term
+            #[doc = concat!(
+                "for each axis in order (16 bytes per axis, ",
+                stringify!($bytes), " bytes). Unlike hashing",
+            )]
+            /// [`resolve`](Self::resolve), this captures the compensation, so a
+            /// checkpoint restored with [`from_le_bytes`](Self::from_le_bytes)
+            /// continues bit-exactly and hashes identically on every platform.
+            #[inline]
+            pub fn to_le_bytes(&self) -> [u8; $bytes] {
+                let mut buf = [0u8; $bytes];
+                let mut chunks = buf.chunks_exact_mut(16);
+                $(chunks.next().unwrap().copy_from_slice(&self.$field.to_le_bytes());)+
+                buf
+            }
+
+            /// Reconstruct an accumulator from little-endian bytes.
+            ///
+            /// This is the inverse of [`to_le_bytes`](Self::to_le_bytes) and is
+            /// required for checkpoint restore and replay branching.
+            #[inline]
+            pub fn from_le_bytes(bytes: [u8; $bytes]) -> Self {
+                let mut chunks = bytes.chunks_exact(16);
+                Self {
+                    $($field: $crate::summation::Neumaier::from_le_bytes(
+                        chunks.next().unwrap().try_into().unwrap(),
+                    ),)+
+                }
+            }
+        }
+
+        impl<S: $crate::summation::Summation> $Acc<S> {
+            /// Create an accumulator with an initial value using strategy `S`.
+            ///
+            /// Equivalent to [`with_initial`](Self::with_initial) for any
+            /// summation strategy; use `default()` for zero.
+            #[inline]
+            pub fn from_initial(initial: $Vector<S::Scalar>) -> Self {
+                Self {
+                    $($field: S::new(initial.$field),)+
+                }
+            }
+
+            /// Add a vector to the accumulator.
+            #[inline]
+            pub fn add(&mut self, vec: $Vector<S::Scalar>) {
+                $(self.$field.add(vec.$field);)+
+            }
+
+            /// Add a scaled vector to the accumulator.
+            ///
+            /// # Note on Compensation
+            ///
+            /// **The scalar multiplication is NOT compensated.** Only the
+            /// accumulation into the internal state uses compensated summation.
+            /// The multiplication `vec.x * scalar` happens in standard
+            /// floating-point arithmetic.
+            ///
+            /// This is standard practice in numerical integration and is
+            /// acceptable for most physics simulations. If you require
+            /// compensated multiplication, use
+            /// [`add_scaled_exact`](Self::add_scaled_exact).
+            #[inline]
+            pub fn add_scaled(&mut self, vec: $Vector<S::Scalar>, scalar: S::Scalar) {
+                $(self.$field.add(vec.$field * scalar);)+
+            }
+
+            /// Add a scaled vector to the accumulator with an error-free product.
+            ///
+            /// Each `vec.x * scalar` is split via FMA-based TwoProduct into its
+            /// rounded value and exact rounding error, and both are fed into
+            /// the compensated sum. The product therefore contributes no
+            /// rounding of its own; the only remaining error is that of the
+            /// summation itself.
+            ///
+            /// Costs one FMA and one extra compensated add per component
+            /// compared to [`add_scaled`](Self::add_scaled).
+            #[inline]
+            pub fn add_scaled_exact(&mut self, vec: $Vector<S::Scalar>, scalar: S::Scalar) {
+                $(
+                    let (product, error) = $crate::eft::two_product(vec.$field, scalar);
+                    self.$field.add(product);
+                    self.$field.add(error);
+                )+
+            }
+
+            /// Fold another accumulator into this one without resolving either.
+            ///
+            /// Both the running sum and the compensation term of each axis of
+            /// `other` are added, so partial sums computed on separate threads
+            /// can be combined without losing precision.
+            #[inline]
+            pub fn merge(&mut self, other: &Self) {
+                $(self.$field.merge(&other.$field);)+
+            }
+
+            /// Merge a slice of partial accumulators in a fixed pairwise tree order.
+            ///
+            /// The slice is split at `len / 2` recursively and the right half
+            /// merged into the left, so the result depends only on the contents
+            /// and order of `parts`. To get identical bits regardless of thread
+            /// count, split the work into a fixed number of chunks (not one per
+            /// thread) and pass the per-chunk accumulators in chunk order.
+            ///
+            /// Returns a zero accumulator for an empty slice.
+            pub fn tree_reduce(parts: &[Self]) -> Self {
+                match parts {
+                    [] => Self::default(),
+                    [single] => single.clone(),
+                    _ => {
+                        let (left, right) = parts.split_at(parts.len() / 2);
+                        let mut acc = Self::tree_reduce(left);
+                        acc.merge(&Self::tree_reduce(right));
+                        acc
+                    }
+                }
+            }
+
+            /// Resolve the accumulator to a standard vector.
+            ///
+            /// This extracts the compensated total from each component.
+            #[inline]
+            pub fn resolve(&self) -> $Vector<S::Scalar> {
+                $Vector {
+                    $($field: self.$field.total(),)+
+                }
+            }
+
+            /// Reset the accumulator to zero.
+            #[inline]
+            pub fn reset(&mut self) {
+                $(self.$field.reset();)+
+            }
+        }
+
+        impl<S: $crate::summation::Summation> Default for $Acc<S> {
+            fn default() -> Self {
+                Self::from_initial($Vector::ZERO)
+            }
+        }
+
+        #[doc = concat!(
+            "`acc += v` is [`acc.add(v)`](", stringify!($Acc), "::add): ",
+            "compensated, not a plain floating-point addition.",
+        )]
+        impl<S: $crate::summation::Summation> std::ops::AddAssign<$Vector<S::Scalar>> for $Acc<S> {
+            #[inline]
+            fn add_assign(&mut self, rhs: $Vector<S::Scalar>) {
+                self.add(rhs);
+            }
+        }
+
+        impl<S: $crate::summation::Summation> Extend<$Vector<S::Scalar>> for $Acc<S> {
+            fn extend<I: IntoIterator<Item = $Vector<S::Scalar>>>(&mut self, iter: I) {
+                iter.into_iter().for_each(|v| self.add(v));
+            }
+        }
+
+        impl<'a, S: $crate::summation::Summation> Extend<&'a $Vector<S::Scalar>> for $Acc<S> {
+            fn extend<I: IntoIterator<Item = &'a $Vector<S::Scalar>>>(&mut self, iter: I) {
+                iter.into_iter().for_each(|v| self.add(*v));
+            }
+        }
+
+        impl<S: $crate::summation::Summation> FromIterator<$Vector<S::Scalar>> for $Acc<S> {
+            fn from_iter<I: IntoIterator<Item = $Vector<S::Scalar>>>(iter: I) -> Self {
+                let mut acc = Self::default();
+                acc.extend(iter);
+                acc
+            }
+        }
+
+        #[doc = concat!(
+            "Compensated summation of vectors into an unresolved accumulator, e.g.\n",
+            "`values.iter().sum::<", stringify!($Acc), ">()`.",
+        )]
+        impl<S: $crate::summation::Summation> std::iter::Sum<$Vector<S::Scalar>> for $Acc<S> {
+            fn sum<I: Iterator<Item = $Vector<S::Scalar>>>(iter: I) -> Self {
+                iter.collect()
+            }
+        }
+
+        impl<'a, S: $crate::summation::Summation> std::iter::Sum<&'a $Vector<S::Scalar>> for $Acc<S> {
+            fn sum<I: Iterator<Item = &'a $Vector<S::Scalar>>>(iter: I) -> Self {
+                let mut acc = Self::default();
+                acc.extend(iter);
+                acc
+            }
+        }
+    };
+}
+
+pub(crate) use {accumulator_impls, vector_impls};
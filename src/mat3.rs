@@ -0,0 +1,282 @@
+//! 3x3 matrices with compensated products.
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+use crate::summation::{Neumaier, Summation};
+use crate::{eft, Vec3};
+
+/// A 3x3 matrix stored in row-major order
+///
+/// `m[row][col]` addresses a single element, and vectors are treated as
+/// columns: `M * v` computes the dot product of each row with `v`.
+///
+/// Every product has a `*_compensated` variant whose inner products are
+/// accumulated with Neumaier summation over error-free (TwoProduct) terms,
+/// the same machinery used by [`Vec3Accumulator`](crate::Vec3Accumulator).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct Mat3 {
+    pub m: [[f64; 3]; 3],
+}
+
+impl Mat3 {
+    /// The zero matrix.
+    pub const ZERO: Self = Self { m: [[0.0; 3]; 3] };
+
+    /// The identity matrix.
+    pub const IDENTITY: Self = Self {
+        m: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+    };
+
+    /// Create a new Mat3 from row-major elements.
+    #[inline]
+    pub const fn new(m: [[f64; 3]; 3]) -> Self {
+        Self { m }
+    }
+
+    /// Create a matrix from three row vectors.
+    #[inline]
+    pub const fn from_rows(r0: Vec3, r1: Vec3, r2: Vec3) -> Self {
+        Self {
+            m: [[r0.x, r0.y, r0.z], [r1.x, r1.y, r1.z], [r2.x, r2.y, r2.z]],
+        }
+    }
+
+    /// Create a matrix from three column vectors.
+    #[inline]
+    pub const fn from_cols(c0: Vec3, c1: Vec3, c2: Vec3) -> Self {
+        Self {
+            m: [[c0.x, c1.x, c2.x], [c0.y, c1.y, c2.y], [c0.z, c1.z, c2.z]],
+        }
+    }
+
+    /// Create a diagonal matrix.
+    #[inline]
+    pub const fn from_diagonal(d: Vec3) -> Self {
+        Self {
+            m: [[d.x, 0.0, 0.0], [0.0, d.y, 0.0], [0.0, 0.0, d.z]],
+        }
+    }
+
+    /// Returns the raw IEEE-754 little-endian bytes in row-major order.
+    ///
+    /// This is the **only valid way** to hash state for determinism verification.
+    /// Do NOT use text formatting (Debug, Display) for hashing—floating-point
+    /// text representation is not guaranteed to be platform-consistent.
+    #[inline]
+    pub fn to_le_bytes(&self) -> [u8; 72] {
+        let mut buf = [0u8; 72];
+        for (i, v) in self.m.iter().flatten().enumerate() {
+            buf[i * 8..i * 8 + 8].copy_from_slice(&v.to_le_bytes());
+        }
+        buf
+    }
+
+    /// Reconstruct a Mat3 from little-endian bytes.
+    ///
+    /// This is the inverse of [`to_le_bytes`](Self::to_le_bytes).
+    #[inline]
+    pub fn from_le_bytes(bytes: [u8; 72]) -> Self {
+        let mut m = [[0.0; 3]; 3];
+        for (i, v) in m.iter_mut().flatten().enumerate() {
+            *v = f64::from_le_bytes(bytes[i * 8..i * 8 + 8].try_into().unwrap());
+        }
+        Self { m }
+    }
+
+    /// Return row `i` as a vector.
+    #[inline]
+    pub fn row(&self, i: usize) -> Vec3 {
+        Vec3::new(self.m[i][0], self.m[i][1], self.m[i][2])
+    }
+
+    /// Return column `j` as a vector.
+    #[inline]
+    pub fn col(&self, j: usize) -> Vec3 {
+        Vec3::new(self.m[0][j], self.m[1][j], self.m[2][j])
+    }
+
+    /// Return the transpose.
+    #[inline]
+    pub fn transpose(&self) -> Self {
+        Self::from_cols(self.row(0), self.row(1), self.row(2))
+    }
+
+    /// Scale every element by a scalar.
+    #[inline]
+    pub fn scale(&self, scalar: f64) -> Self {
+        Self::from_rows(
+            self.row(0).scale(scalar),
+            self.row(1).scale(scalar),
+            self.row(2).scale(scalar),
+        )
+    }
+
+    /// Compute the determinant as the scalar triple product of the rows.
+    #[inline]
+    pub fn determinant(&self) -> f64 {
+        self.row(0).dot(self.row(1).cross(self.row(2)))
+    }
+
+    /// Compute the determinant with compensated arithmetic.
+    ///
+    /// The cofactors use Kahan's difference of products and the final
+    /// expansion is a compensated inner product.
+    #[inline]
+    pub fn determinant_compensated(&self) -> f64 {
+        dot_neumaier(self.row(0), self.row(1).cross_compensated(self.row(2)))
+    }
+
+    /// Compute the inverse, or `None` if the matrix is singular.
+    ///
+    /// A matrix is treated as singular when its determinant is zero or
+    /// not finite; no tolerance is applied.
+    #[inline]
+    pub fn inverse(&self) -> Option<Self> {
+        let (r0, r1, r2) = (self.row(0), self.row(1), self.row(2));
+        let c0 = r1.cross(r2);
+        let det = r0.dot(c0);
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        Some(Self::from_cols(c0, r2.cross(r0), r0.cross(r1)).scale(1.0 / det))
+    }
+
+    /// Compute the inverse with compensated cofactors and determinant.
+    ///
+    /// See [`inverse`](Self::inverse) for the singularity rule.
+    #[inline]
+    pub fn inverse_compensated(&self) -> Option<Self> {
+        let (r0, r1, r2) = (self.row(0), self.row(1), self.row(2));
+        let c0 = r1.cross_compensated(r2);
+        let det = dot_neumaier(r0, c0);
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let adj = Self::from_cols(c0, r2.cross_compensated(r0), r0.cross_compensated(r1));
+        Some(adj.scale(1.0 / det))
+    }
+
+    /// Multiply a column vector: `M * v`.
+    #[inline]
+    pub fn mul_vec3(&self, v: Vec3) -> Vec3 {
+        Vec3::new(self.row(0).dot(v), self.row(1).dot(v), self.row(2).dot(v))
+    }
+
+    /// Multiply a column vector with compensated inner products.
+    #[inline]
+    pub fn mul_vec3_compensated(&self, v: Vec3) -> Vec3 {
+        Vec3::new(
+            dot_neumaier(self.row(0), v),
+            dot_neumaier(self.row(1), v),
+            dot_neumaier(self.row(2), v),
+        )
+    }
+
+    /// Multiply by another matrix: `self * rhs`.
+    #[inline]
+    pub fn mul_mat3(&self, rhs: Mat3) -> Mat3 {
+        Self::from_cols(
+            self.mul_vec3(rhs.col(0)),
+            self.mul_vec3(rhs.col(1)),
+            self.mul_vec3(rhs.col(2)),
+        )
+    }
+
+    /// Multiply by another matrix with compensated inner products.
+    #[inline]
+    pub fn mul_mat3_compensated(&self, rhs: Mat3) -> Mat3 {
+        Self::from_cols(
+            self.mul_vec3_compensated(rhs.col(0)),
+            self.mul_vec3_compensated(rhs.col(1)),
+            self.mul_vec3_compensated(rhs.col(2)),
+        )
+    }
+}
+
+impl Default for Mat3 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl std::ops::Mul for Mat3 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        self.mul_mat3(rhs)
+    }
+}
+
+impl std::ops::Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+    fn mul(self, rhs: Vec3) -> Vec3 {
+        self.mul_vec3(rhs)
+    }
+}
+
+/// Inner product with each term split by TwoProduct and summed by Neumaier.
+#[inline]
+fn dot_neumaier(a: Vec3, b: Vec3) -> f64 {
+    let mut acc = Neumaier::new(0.0);
+    for (x, y) in [(a.x, b.x), (a.y, b.y), (a.z, b.z)] {
+        let (p, e) = eft::two_product(x, y);
+        acc.add(p);
+        acc.add(e);
+    }
+    acc.total()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Mat3 {
+        Mat3::new([[2.0, -1.0, 0.5], [0.0, 3.0, 1.0], [4.0, 0.25, -2.0]])
+    }
+
+    #[test]
+    fn mat3_to_from_le_bytes_roundtrip() {
+        let original = sample();
+        let restored = Mat3::from_le_bytes(original.to_le_bytes());
+        assert_eq!(original, restored);
+        assert_eq!(original.to_le_bytes()[8..16], (-1.0f64).to_le_bytes());
+    }
+
+    #[test]
+    fn mat3_inverse_roundtrip() {
+        let m = sample();
+        let inv = m.inverse_compensated().unwrap();
+        let id = m.mul_mat3_compensated(inv);
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((id.m[i][j] - expected).abs() < 1e-15);
+            }
+        }
+        assert_eq!(m.determinant(), m.determinant_compensated());
+        let singular = Mat3::from_diagonal(Vec3::new(1.0, 0.0, 1.0));
+        assert!(singular.inverse().is_none());
+    }
+
+    #[test]
+    fn mat3_row_major_mul_vec3() {
+        let m = sample();
+        assert_eq!(m * Vec3::new(1.0, 0.0, 0.0), m.col(0));
+        assert_eq!(m.transpose().row(0), m.col(0));
+        assert_eq!(Mat3::IDENTITY * m, m);
+    }
+
+    #[test]
+    fn mat3_mul_vec3_compensated_cancellation() {
+        // Row 0 dotted with v is 1e16 + 1 - 1e16; naive evaluation returns 0.
+        let m = Mat3::from_rows(
+            Vec3::new(1e8, 1.0, -1e8),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        );
+        let v = Vec3::new(1e8, 1.0, 1e8);
+        assert_eq!(m.mul_vec3(v).x, 0.0);
+        assert_eq!(m.mul_vec3_compensated(v).x, 1.0);
+    }
+}
@@ -0,0 +1,520 @@
+//! Barnes–Hut octree for approximate O(N log N) gravity.
+
+use std::ops::Range;
+
+use crate::summation::{Neumaier, Summation};
+use crate::{Vec3, Vec3Accumulator};
+
+/// Quantization bits per axis; three axes fill a 63-bit Morton key.
+const DEPTH: u32 = 21;
+
+/// A Barnes–Hut octree over a set of point masses
+///
+/// Positions are quantized to a 2^21 grid over a power-of-two bounding cube
+/// and sorted by Morton key, with ties broken by the exact position and mass
+/// bits. The tree is built over that sorted order, so its shape, child
+/// ordering and every compensated sum inside it depend only on the set of
+/// bodies, not on the order they were given in.
+///
+/// [`acceleration`](Self::acceleration) reads the tree without mutating it,
+/// so the bodies can be split across any number of threads and each result
+/// is still bit-identical.
+///
+/// # Example
+///
+/// ```rust
+/// use drift_linalg::nbody::Octree;
+/// use drift_linalg::Vec3;
+///
+/// let positions: Vec<Vec3> = (0..1000)
+///     .map(|i| {
+///         let t = i as f64;
+///         Vec3::new((0.7 * t).sin(), (1.3 * t).cos(), (0.1 * t).sin()) * 10.0
+///     })
+///     .collect();
+/// let masses = vec![1.0; positions.len()];
+///
+/// let tree = Octree::new(&positions, &masses);
+/// assert_eq!(tree.total_mass(), 1000.0);
+///
+/// let approx = tree.acceleration(0, 0.5, 1.0, 0.01);
+/// let exact = tree.acceleration(0, 0.0, 1.0, 0.01);
+/// assert!((approx - exact).magnitude() < 1e-2 * exact.magnitude());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Octree {
+    nodes: Vec<Node>,
+    /// Body indices in Morton order.
+    order: Vec<usize>,
+    /// Position of each body in `order`.
+    rank: Vec<usize>,
+    positions: Vec<Vec3>,
+    masses: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    /// The bodies in this node, as a range of `order`.
+    bodies: Range<usize>,
+    /// The non-empty children in octant order, as a range of `nodes`.
+    children: Range<usize>,
+    /// Edge length of the node's cube.
+    size: f64,
+    mass: f64,
+    center_of_mass: Vec3,
+}
+
+impl Octree {
+    /// Build a tree over bodies at `positions` with the given `masses`.
+    ///
+    /// Bodies with a NaN or infinite coordinate are kept, but do not widen
+    /// the bounding cube; they are placed on its faces.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slices differ in length.
+    pub fn new(positions: &[Vec3], masses: &[f64]) -> Self {
+        assert_eq!(positions.len(), masses.len(), "one mass per position");
+        let (min, size) = bounding_cube(positions);
+        let scale = (1u64 << DEPTH) as f64 / size;
+        let keys: Vec<u64> = positions
+            .iter()
+            .map(|&p| {
+                let q = |x: f64, m: f64| (((x - m) * scale) as u64).min((1 << DEPTH) - 1);
+                morton(q(p.x, min.x), q(p.y, min.y), q(p.z, min.z))
+            })
+            .collect();
+
+        let mut order: Vec<usize> = (0..positions.len()).collect();
+        order.sort_unstable_by(|&a, &b| {
+            let (pa, pb) = (positions[a], positions[b]);
+            keys[a]
+                .cmp(&keys[b])
+                .then_with(|| pa.x.total_cmp(&pb.x))
+                .then_with(|| pa.y.total_cmp(&pb.y))
+                .then_with(|| pa.z.total_cmp(&pb.z))
+                .then_with(|| masses[a].total_cmp(&masses[b]))
+        });
+        let mut rank = vec![0; order.len()];
+        for (r, &i) in order.iter().enumerate() {
+            rank[i] = r;
+        }
+        let sorted_keys: Vec<u64> = order.iter().map(|&i| keys[i]).collect();
+
+        let mut tree = Self {
+            nodes: Vec::new(),
+            order,
+            rank,
+            positions: positions.to_vec(),
+            masses: masses.to_vec(),
+        };
+        if !positions.is_empty() {
+            tree.nodes.push(tree.node(0..positions.len(), size));
+            tree.split(0, &sorted_keys, 0);
+        }
+        tree
+    }
+
+    /// The number of bodies.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Whether the tree has no bodies.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// The total mass of all bodies.
+    #[inline]
+    pub fn total_mass(&self) -> f64 {
+        self.nodes.first().map_or(0.0, |n| n.mass)
+    }
+
+    /// The center of mass of all bodies, or zero for an empty tree.
+    #[inline]
+    pub fn center_of_mass(&self) -> Vec3 {
+        self.nodes.first().map_or(Vec3::ZERO, |n| n.center_of_mass)
+    }
+
+    /// The gravitational acceleration of body `index` due to all others.
+    ///
+    /// A node of edge length `l` whose center of mass is at distance `d` is
+    /// treated as a single mass when `l < theta · d`; otherwise its children
+    /// are visited in octant order. A node containing the body itself is
+    /// always opened. `theta = 0` opens every node, giving the direct sum;
+    /// only `theta²` is used, so a negative `theta` acts like its magnitude,
+    /// and a NaN one also opens every node.
+    /// The force law is the Plummer-softened one of
+    /// [`System`](super::System).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= self.len()`.
+    pub fn acceleration(&self, index: usize, theta: f64, g: f64, softening: f64) -> Vec3 {
+        assert!(index < self.len(), "body index out of range");
+        let mut acc = Vec3Accumulator::new();
+        if !self.nodes.is_empty() {
+            let query = Query {
+                index,
+                rank: self.rank[index],
+                position: self.positions[index],
+                theta2: theta * theta,
+                g,
+                eps2: softening * softening,
+            };
+            self.visit(0, &query, &mut acc);
+        }
+        acc.resolve()
+    }
+
+    /// The accelerations of all bodies; see [`acceleration`](Self::acceleration).
+    pub fn accelerations(&self, theta: f64, g: f64, softening: f64) -> Vec<Vec3> {
+        (0..self.len())
+            .map(|i| self.acceleration(i, theta, g, softening))
+            .collect()
+    }
+
+    /// A node over `bodies` with its compensated mass and center of mass.
+    fn node(&self, bodies: Range<usize>, size: f64) -> Node {
+        let mut mass = Neumaier::new(0.0);
+        let mut moment = Vec3Accumulator::new();
+        for &i in &self.order[bodies.clone()] {
+            mass.add(self.masses[i]);
+            moment.add_scaled_exact(self.positions[i], self.masses[i]);
+        }
+        let mass = mass.total();
+        let center_of_mass = if mass == 0.0 {
+            self.positions[self.order[bodies.start]]
+        } else {
+            moment.resolve() / mass
+        };
+        Node {
+            children: 0..0,
+            bodies,
+            size,
+            mass,
+            center_of_mass,
+        }
+    }
+
+    /// Create the children of `node`, at `depth`, and recurse into them.
+    fn split(&mut self, node: usize, keys: &[u64], depth: u32) {
+        let bodies = self.nodes[node].bodies.clone();
+        if bodies.len() <= 1 || depth == DEPTH {
+            return;
+        }
+        let shift = 3 * (DEPTH - 1 - depth);
+        let size = self.nodes[node].size / 2.0;
+        let first = self.nodes.len();
+        let mut start = bodies.start;
+        while start < bodies.end {
+            let octant = (keys[start] >> shift) & 7;
+            let end =
+                start + keys[start..bodies.end].partition_point(|k| (k >> shift) & 7 == octant);
+            let child = self.node(start..end, size);
+            self.nodes.push(child);
+            start = end;
+        }
+        self.nodes[node].children = first..self.nodes.len();
+        for child in first..self.nodes.len() {
+            self.split(child, keys, depth + 1);
+        }
+    }
+
+    fn visit(&self, node: usize, query: &Query, acc: &mut Vec3Accumulator) {
+        let n = &self.nodes[node];
+        let contains = n.bodies.contains(&query.rank);
+        if !contains {
+            let d = n.center_of_mass - query.position;
+            let r2 = d.magnitude_squared_compensated();
+            if n.size * n.size < query.theta2 * r2 {
+                query.pull(acc, d, r2, n.mass);
+                return;
+            }
+        }
+        if n.children.is_empty() {
+            for &i in &self.order[n.bodies.clone()] {
+                if i != query.index {
+                    let d = self.positions[i] - query.position;
+                    let r2 = d.magnitude_squared_compensated();
+                    query.pull(acc, d, r2, self.masses[i]);
+                }
+            }
+        } else {
+            for child in n.children.clone() {
+                self.visit(child, query, acc);
+            }
+        }
+    }
+}
+
+/// The body whose acceleration is being summed, and the force law.
+struct Query {
+    index: usize,
+    rank: usize,
+    position: Vec3,
+    theta2: f64,
+    g: f64,
+    eps2: f64,
+}
+
+impl Query {
+    /// Add the pull of `mass` at offset `d`, with `r2 = |d|²`.
+    #[inline]
+    fn pull(&self, acc: &mut Vec3Accumulator, d: Vec3, r2: f64, mass: f64) {
+        let s = r2 + self.eps2;
+        if s != 0.0 {
+            acc.add_scaled_exact(d.scale(self.g / (s * s.sqrt())), mass);
+        }
+    }
+}
+
+/// The minimum corner and a power-of-two edge length of a cube containing
+/// every finite position.
+///
+/// Bodies with a non-finite coordinate are left out of the bounds; their
+/// keys saturate to a face of the cube, so the tree is still built. The
+/// edge length is capped at 2^1023, so a cube spanning more than that (e.g.
+/// bodies at ±1e308) does not contain every finite position either, and its
+/// far bodies are clamped to the last cell in the same way.
+fn bounding_cube(positions: &[Vec3]) -> (Vec3, f64) {
+    let finite = || {
+        positions
+            .iter()
+            .filter(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite())
+    };
+    let fold = |f: fn(f64, f64) -> f64, init: f64| {
+        finite().fold(Vec3::new(init, init, init), |a, p| {
+            Vec3::new(f(a.x, p.x), f(a.y, p.y), f(a.z, p.z))
+        })
+    };
+    let (min, max) = (
+        fold(f64::min, f64::INFINITY),
+        fold(f64::max, f64::NEG_INFINITY),
+    );
+    if finite().next().is_none() {
+        return (Vec3::ZERO, 1.0);
+    }
+    // May overflow to infinity, which `power_of_two_at_least` caps.
+    let extent = (max.x - min.x).max(max.y - min.y).max(max.z - min.z);
+    // `+ 0.0` maps -0.0 to 0.0, which `min` may return for either sign.
+    (min + Vec3::ZERO, power_of_two_at_least(extent))
+}
+
+/// The smallest power of two at least `extent`, read off the exponent bits,
+/// or 1 for a zero extent.
+///
+/// The result is kept within [2^(DEPTH - 1023), 2^1023], so that both it and
+/// the quantization scale `2^DEPTH / size` are finite.
+fn power_of_two_at_least(extent: f64) -> f64 {
+    const MANTISSA: u64 = (1 << 52) - 1;
+    let exponent = if extent == 0.0 {
+        0
+    } else {
+        let bits = extent.to_bits();
+        let exponent = (bits >> 52) as i32 - 1023;
+        if bits & MANTISSA == 0 {
+            exponent
+        } else {
+            exponent + 1
+        }
+    };
+    let exponent = exponent.clamp(DEPTH as i32 - 1023, 1023);
+    f64::from_bits(((exponent + 1023) as u64) << 52)
+}
+
+/// Interleave the low 21 bits of each coordinate, `x` lowest.
+fn morton(x: u64, y: u64, z: u64) -> u64 {
+    fn spread(mut v: u64) -> u64 {
+        v &= 0x1f_ffff;
+        v = (v | v << 32) & 0x001f_0000_0000_ffff;
+        v = (v | v << 16) & 0x001f_0000_ff00_00ff;
+        v = (v | v << 8) & 0x100f_00f0_0f00_f00f;
+        v = (v | v << 4) & 0x10c3_0c30_c30c_30c3;
+        v = (v | v << 2) & 0x1249_2492_4924_9249;
+        v
+    }
+    spread(x) | spread(y) << 1 | spread(z) << 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbody::System;
+
+    /// A deterministic pseudo-random cluster of `n` bodies.
+    fn cluster(n: usize) -> (Vec<Vec3>, Vec<f64>) {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+        (0..n)
+            .map(|_| {
+                let p = Vec3::new(next(), next(), next()) - Vec3::new(0.5, 0.5, 0.5);
+                (p.scale(100.0), 1.0 + next())
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn zero_opening_angle_matches_direct_sum() {
+        let (positions, masses) = cluster(200);
+        let mut system = System::new(1.0, 0.1).unwrap();
+        for (&p, &m) in positions.iter().zip(&masses) {
+            system.add_body(m, p, Vec3::ZERO).unwrap();
+        }
+        let direct = system.accelerations();
+        let tree = Octree::new(&positions, &masses).accelerations(0.0, 1.0, 0.1);
+        for (a, b) in direct.iter().zip(&tree) {
+            assert!(
+                (*a - *b).magnitude() <= 1e-14 * a.magnitude(),
+                "{a:?} {b:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn opening_angle_bounds_force_error() {
+        let (positions, masses) = cluster(2000);
+        let tree = Octree::new(&positions, &masses);
+        let exact = tree.accelerations(0.0, 1.0, 0.1);
+        let approx = tree.accelerations(0.5, 1.0, 0.1);
+        // Root-mean-square relative error; individual bodies near the centre,
+        // where the pulls nearly cancel, can be off by several percent.
+        let sum: f64 = exact
+            .iter()
+            .zip(&approx)
+            .map(|(a, b)| ((*a - *b).magnitude() / a.magnitude()).powi(2))
+            .sum();
+        let rms = (sum / exact.len() as f64).sqrt();
+        assert!(rms < 1e-2, "{rms}");
+        assert!(rms > 0.0);
+    }
+
+    #[test]
+    fn result_is_independent_of_insertion_order() {
+        let (positions, masses) = cluster(500);
+        let forward = Octree::new(&positions, &masses);
+        let reversed: (Vec<Vec3>, Vec<f64>) = positions
+            .iter()
+            .copied()
+            .zip(masses.iter().copied())
+            .rev()
+            .unzip();
+        let backward = Octree::new(&reversed.0, &reversed.1);
+
+        assert_eq!(
+            forward.total_mass().to_bits(),
+            backward.total_mass().to_bits()
+        );
+        assert_eq!(
+            forward.center_of_mass().to_le_bytes(),
+            backward.center_of_mass().to_le_bytes()
+        );
+        let n = positions.len();
+        for i in 0..n {
+            let a = forward.acceleration(i, 0.6, 1.0, 0.05);
+            let b = backward.acceleration(n - 1 - i, 0.6, 1.0, 0.05);
+            assert_eq!(a.to_le_bytes(), b.to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn result_is_independent_of_thread_count() {
+        let (positions, masses) = cluster(1000);
+        let tree = Octree::new(&positions, &masses);
+        let serial = tree.accelerations(0.5, 1.0, 0.05);
+        for threads in [2, 3, 8] {
+            let chunk = positions.len().div_ceil(threads);
+            let parallel: Vec<Vec3> = std::thread::scope(|scope| {
+                let handles: Vec<_> = (0..positions.len())
+                    .step_by(chunk)
+                    .map(|start| {
+                        let tree = &tree;
+                        scope.spawn(move || {
+                            (start..(start + chunk).min(tree.len()))
+                                .map(|i| tree.acceleration(i, 0.5, 1.0, 0.05))
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .flat_map(|h| h.join().unwrap())
+                    .collect()
+            });
+            assert_eq!(parallel, serial);
+        }
+    }
+
+    #[test]
+    fn coincident_bodies_stop_at_maximum_depth() {
+        let positions = vec![Vec3::new(1.0, 2.0, 3.0); 4];
+        let tree = Octree::new(&positions, &[1.0; 4]);
+        assert_eq!(tree.total_mass(), 4.0);
+        assert_eq!(tree.center_of_mass(), positions[0]);
+        assert_eq!(tree.acceleration(0, 0.5, 1.0, 0.0), Vec3::ZERO);
+        assert!(Octree::new(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn bounding_cube_is_a_capped_power_of_two() {
+        let cube = |xs: &[f64]| {
+            let positions: Vec<Vec3> = xs.iter().map(|&x| Vec3::new(x, 0.0, 0.0)).collect();
+            bounding_cube(&positions)
+        };
+        assert_eq!(cube(&[1.0, 4.0]), (Vec3::new(1.0, 0.0, 0.0), 4.0));
+        assert_eq!(cube(&[0.0, 3.0]).1, 4.0);
+        assert_eq!(cube(&[0.0, 0.3]).1, 0.5);
+        assert_eq!(cube(&[2.0, 2.0]).1, 1.0);
+        assert_eq!(cube(&[0.0, 1e-310]).1, 2f64.powi(DEPTH as i32 - 1023));
+        assert_eq!(cube(&[-1e308, 1e308]).1, 2f64.powi(1023));
+        assert_eq!(cube(&[f64::MIN, f64::MAX]).1, 2f64.powi(1023));
+
+        // Non-finite coordinates are left out of the bounds.
+        assert_eq!(
+            cube(&[1.0, f64::INFINITY, 3.0, f64::NAN]),
+            (Vec3::new(1.0, 0.0, 0.0), 2.0)
+        );
+        assert_eq!(cube(&[f64::NEG_INFINITY, f64::NAN]), (Vec3::ZERO, 1.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "body index out of range")]
+    fn acceleration_panics_on_out_of_range_index() {
+        let tree = Octree::new(&[], &[]);
+        tree.acceleration(0, 0.5, 1.0, 0.1);
+    }
+
+    #[test]
+    fn extreme_and_non_finite_positions_do_not_hang() {
+        let positions = [
+            Vec3::new(1e308, 0.0, 0.0),
+            Vec3::new(-1e308, 0.0, 0.0),
+            Vec3::new(0.0, f64::MAX, f64::MIN),
+            Vec3::new(1.0, 2.0, 3.0),
+        ];
+        let tree = Octree::new(&positions, &[1.0; 4]);
+        assert_eq!(tree.total_mass(), 4.0);
+        assert_eq!(tree.accelerations(0.5, 1.0, 0.1).len(), 4);
+
+        // A single runaway body must not stall a Barnes–Hut step.
+        let mut system = System::new(1.0, 0.1).unwrap();
+        system.add_body(1.0, Vec3::ZERO, Vec3::ZERO).unwrap();
+        system
+            .add_body(1.0, Vec3::new(1.0, 0.0, 0.0), Vec3::ZERO)
+            .unwrap();
+        system
+            .add_body(1.0, Vec3::new(f64::INFINITY, 0.0, f64::NAN), Vec3::ZERO)
+            .unwrap();
+        assert!(system.set_opening_angle(Some(0.5)));
+        system.step(1e-3);
+        assert_eq!(system.len(), 3);
+    }
+}
@@ -0,0 +1,474 @@
+//! Quaternions and drift-free orientation accumulation.
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+use crate::summation::{Neumaier, Summation};
+use crate::{eft, Vec3};
+
+/// A quaternion `w + xi + yj + zk`
+///
+/// Unit quaternions represent orientations. Rotation follows the Hamilton
+/// convention: `a * b` applies `b` first, then `a`.
+///
+/// For integrating angular velocity over many steps, use
+/// [`QuatAccumulator`] instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct Quat {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quat {
+    /// The identity rotation.
+    pub const IDENTITY: Self = Self { w: 1.0, x: 0.0, y: 0.0, z: 0.0 };
+
+    /// Create a new Quat.
+    #[inline]
+    pub const fn new(w: f64, x: f64, y: f64, z: f64) -> Self {
+        Self { w, x, y, z }
+    }
+
+    /// Create a rotation of `angle` radians about `axis`.
+    ///
+    /// `axis` must be non-zero; it is normalized internally.
+    #[inline]
+    pub fn from_axis_angle(axis: Vec3, angle: f64) -> Self {
+        let (s, c) = (angle * 0.5).sin_cos();
+        let v = axis.scale(s / axis.magnitude());
+        Self {
+            w: c,
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+
+    /// Returns the raw IEEE-754 little-endian bytes in `w, x, y, z` order.
+    ///
+    /// This is the **only valid way** to hash state for determinism verification.
+    /// Do NOT use text formatting (Debug, Display) for hashing—floating-point
+    /// text representation is not guaranteed to be platform-consistent.
+    #[inline]
+    pub fn to_le_bytes(&self) -> [u8; 32] {
+        let mut buf = [0u8; 32];
+        buf[0..8].copy_from_slice(&self.w.to_le_bytes());
+        buf[8..16].copy_from_slice(&self.x.to_le_bytes());
+        buf[16..24].copy_from_slice(&self.y.to_le_bytes());
+        buf[24..32].copy_from_slice(&self.z.to_le_bytes());
+        buf
+    }
+
+    /// Reconstruct a Quat from little-endian bytes.
+    ///
+    /// This is the inverse of [`to_le_bytes`](Self::to_le_bytes).
+    #[inline]
+    pub fn from_le_bytes(bytes: [u8; 32]) -> Self {
+        Self {
+            w: f64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+            x: f64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+            y: f64::from_le_bytes(bytes[16..24].try_into().unwrap()),
+            z: f64::from_le_bytes(bytes[24..32].try_into().unwrap()),
+        }
+    }
+
+    /// The vector (imaginary) part.
+    #[inline]
+    pub fn vector(&self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+
+    /// Compute the four-component dot product with another quaternion.
+    #[inline]
+    pub fn dot(&self, other: Quat) -> f64 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Compute the squared norm (avoids sqrt).
+    #[inline]
+    pub fn norm_squared(&self) -> f64 {
+        self.dot(*self)
+    }
+
+    /// Compute the norm.
+    #[inline]
+    pub fn norm(&self) -> f64 {
+        self.norm_squared().sqrt()
+    }
+
+    /// Compute the squared norm with compensated arithmetic.
+    #[inline]
+    pub fn norm_squared_compensated(&self) -> f64 {
+        let c = [self.w, self.x, self.y, self.z];
+        eft::dot2(&c, &c)
+    }
+
+    /// Return the conjugate `w - xi - yj - zk`.
+    ///
+    /// For a unit quaternion this is the inverse rotation.
+    #[inline]
+    pub fn conjugate(&self) -> Self {
+        Self {
+            w: self.w,
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+
+    /// Return this quaternion scaled to unit norm.
+    ///
+    /// The norm is computed with compensated arithmetic so that the result
+    /// is as close to the unit sphere as a single division allows. A zero
+    /// or non-finite quaternion has no direction and gives NaN components;
+    /// use [`try_normalize`](Self::try_normalize) to detect that case.
+    #[inline]
+    pub fn normalize(&self) -> Self {
+        self.scale(1.0 / self.norm_squared_compensated().sqrt())
+    }
+
+    /// Return this quaternion scaled to unit norm, or `None` if its squared
+    /// norm is zero or not finite.
+    ///
+    /// Components so small or so large that the squared norm underflows to
+    /// zero or overflows are rejected too.
+    #[inline]
+    pub fn try_normalize(&self) -> Option<Self> {
+        let n2 = self.norm_squared_compensated();
+        if n2 > 0.0 && n2.is_finite() {
+            Some(self.scale(1.0 / n2.sqrt()))
+        } else {
+            None
+        }
+    }
+
+    /// Scale every component by a scalar.
+    #[inline]
+    pub fn scale(&self, scalar: f64) -> Self {
+        Self {
+            w: self.w * scalar,
+            x: self.x * scalar,
+            y: self.y * scalar,
+            z: self.z * scalar,
+        }
+    }
+
+    /// Rotate a vector by this quaternion.
+    ///
+    /// Assumes a unit quaternion. Uses `v + 2w(u × v) + 2u × (u × v)` with
+    /// `u` the vector part, which avoids forming the full sandwich product.
+    #[inline]
+    pub fn rotate(&self, v: Vec3) -> Vec3 {
+        let u = self.vector();
+        let t = u.cross(v).scale(2.0);
+        v + t.scale(self.w) + u.cross(t)
+    }
+}
+
+impl Default for Quat {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl std::ops::Mul for Quat {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            w: self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+            x: self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            y: self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            z: self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+        }
+    }
+}
+
+impl std::ops::Neg for Quat {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self {
+            w: -self.w,
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+/// A drift-free orientation accumulator
+///
+/// Uses Neumaier-compensated summation on each quaternion component. Each
+/// integration step adds the increment `(Δ - 1) q`, where `Δ` is the Cayley
+/// transform of the rotation `ω dt`. The Cayley transform is exactly unit
+/// norm in real arithmetic and uses only `+ - * /`, so steps are
+/// bit-reproducible across platforms (no `sin`/`cos`).
+///
+/// When the squared norm drifts more than [`RENORMALIZE_TOLERANCE`](Self::RENORMALIZE_TOLERANCE)
+/// from one, the accumulator is renormalized. The check uses the compensated
+/// norm, so the decision is identical on every platform.
+///
+/// # Example
+///
+/// ```rust
+/// use drift_linalg::{QuatAccumulator, Vec3};
+///
+/// let mut orientation = QuatAccumulator::new();
+/// let omega = Vec3::new(0.0, 0.0, 10.0);
+///
+/// for _ in 0..100_000 {
+///     orientation.integrate(omega, 1.0 / 60.0);
+/// }
+///
+/// let q = orientation.resolve();
+/// assert!((q.norm() - 1.0).abs() < 1e-12);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct QuatAccumulator {
+    w: Neumaier,
+    x: Neumaier,
+    y: Neumaier,
+    z: Neumaier,
+}
+
+impl QuatAccumulator {
+    /// Maximum allowed deviation of the squared norm from one before the
+    /// accumulator is renormalized.
+    pub const RENORMALIZE_TOLERANCE: f64 = 1e-12;
+
+    /// Create an accumulator at the identity orientation.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an accumulator with an initial orientation.
+    ///
+    /// The initial value is normalized with [`Quat::normalize`], so a zero
+    /// or non-finite `initial` gives an accumulator whose every component is
+    /// NaN. Check the orientation with [`Quat::try_normalize`] first if it
+    /// comes from untrusted input.
+    #[inline]
+    pub fn with_initial(initial: Quat) -> Self {
+        let q = initial.normalize();
+        Self {
+            w: Neumaier::new(q.w),
+            x: Neumaier::new(q.x),
+            y: Neumaier::new(q.y),
+            z: Neumaier::new(q.z),
+        }
+    }
+
+    /// Returns the full accumulator state as little-endian bytes.
+    ///
+    /// The layout is the running sum followed by the compensation term for
+    /// each of w, x, y and z (8 × f64, 64 bytes), so a restored accumulator
+    /// continues bit-exactly.
+    #[inline]
+    pub fn to_le_bytes(&self) -> [u8; 64] {
+        let mut buf = [0u8; 64];
+        buf[0..16].copy_from_slice(&self.w.to_le_bytes());
+        buf[16..32].copy_from_slice(&self.x.to_le_bytes());
+        buf[32..48].copy_from_slice(&self.y.to_le_bytes());
+        buf[48..64].copy_from_slice(&self.z.to_le_bytes());
+        buf
+    }
+
+    /// Reconstruct an accumulator from little-endian bytes.
+    ///
+    /// This is the inverse of [`to_le_bytes`](Self::to_le_bytes). The state is
+    /// restored as-is, without normalizing.
+    #[inline]
+    pub fn from_le_bytes(bytes: [u8; 64]) -> Self {
+        Self {
+            w: Neumaier::from_le_bytes(bytes[0..16].try_into().unwrap()),
+            x: Neumaier::from_le_bytes(bytes[16..32].try_into().unwrap()),
+            y: Neumaier::from_le_bytes(bytes[32..48].try_into().unwrap()),
+            z: Neumaier::from_le_bytes(bytes[48..64].try_into().unwrap()),
+        }
+    }
+
+    /// Add a raw quaternion increment to each component.
+    ///
+    /// No renormalization is applied; most callers want
+    /// [`integrate`](Self::integrate) instead.
+    #[inline]
+    pub fn add(&mut self, delta: Quat) {
+        self.w.add(delta.w);
+        self.x.add(delta.x);
+        self.y.add(delta.y);
+        self.z.add(delta.z);
+    }
+
+    /// Integrate a world-frame angular velocity over `dt`.
+    ///
+    /// Applies `q ← Δ q`, where `Δ` rotates by approximately `|ω| dt` about `ω`.
+    #[inline]
+    pub fn integrate(&mut self, omega: Vec3, dt: f64) {
+        let (d, q) = (cayley_minus_one(omega, dt), self.resolve());
+        self.add(d * q);
+        self.renormalize_if_needed();
+    }
+
+    /// Integrate a body-frame angular velocity over `dt`.
+    ///
+    /// Applies `q ← q Δ`, where `Δ` rotates by approximately `|ω| dt` about `ω`.
+    #[inline]
+    pub fn integrate_body(&mut self, omega: Vec3, dt: f64) {
+        let (d, q) = (cayley_minus_one(omega, dt), self.resolve());
+        self.add(q * d);
+        self.renormalize_if_needed();
+    }
+
+    /// Force renormalization to unit norm.
+    ///
+    /// This resolves the accumulator and restarts it from the normalized
+    /// value, so the compensation terms are folded in first.
+    #[inline]
+    pub fn renormalize(&mut self) {
+        *self = Self::with_initial(self.resolve());
+    }
+
+    /// Resolve the accumulator to a standard Quat.
+    ///
+    /// This extracts the compensated total from each component.
+    #[inline]
+    pub fn resolve(&self) -> Quat {
+        Quat {
+            w: self.w.total(),
+            x: self.x.total(),
+            y: self.y.total(),
+            z: self.z.total(),
+        }
+    }
+
+    /// Reset the accumulator to the identity orientation.
+    #[inline]
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    #[inline]
+    fn renormalize_if_needed(&mut self) {
+        let n2 = self.resolve().norm_squared_compensated();
+        if (n2 - 1.0).abs() > Self::RENORMALIZE_TOLERANCE {
+            self.renormalize();
+        }
+    }
+}
+
+impl Default for QuatAccumulator {
+    fn default() -> Self {
+        Self {
+            w: Neumaier::new(1.0),
+            x: Neumaier::new(0.0),
+            y: Neumaier::new(0.0),
+            z: Neumaier::new(0.0),
+        }
+    }
+}
+
+/// The Cayley rotation for `ω dt`, minus the identity.
+///
+/// With `u = ω dt / 4`, the Cayley transform is `((1 - |u|²) + 2u) / (1 + |u|²)`.
+/// Subtracting one analytically keeps the small increment free of cancellation.
+#[inline]
+fn cayley_minus_one(omega: Vec3, dt: f64) -> Quat {
+    let u = omega.scale(dt * 0.25);
+    let u2 = u.magnitude_squared_compensated();
+    let k = 2.0 / (1.0 + u2);
+    Quat {
+        w: -u2 * k,
+        x: u.x * k,
+        y: u.y * k,
+        z: u.z * k,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quat_to_from_le_bytes_roundtrip() {
+        let original = Quat::new(0.5, -0.5, 0.25, -0.125);
+        let restored = Quat::from_le_bytes(original.to_le_bytes());
+        assert_eq!(original, restored);
+    }
+
+    #[test]
+    fn quat_rotate_matches_sandwich_product() {
+        let q = Quat::from_axis_angle(Vec3::new(1.0, 2.0, 3.0), 0.7);
+        let v = Vec3::new(-2.0, 0.5, 4.0);
+        let r = q.rotate(v);
+        let s = q * Quat::new(0.0, v.x, v.y, v.z) * q.conjugate();
+        assert!((r - s.vector()).magnitude() < 1e-14);
+        assert!((r.magnitude() - v.magnitude()).abs() < 1e-14);
+    }
+
+    #[test]
+    fn quat_rotate_quarter_turn() {
+        let q = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), std::f64::consts::FRAC_PI_2);
+        let r = q.rotate(Vec3::new(1.0, 0.0, 0.0));
+        assert!((r - Vec3::new(0.0, 1.0, 0.0)).magnitude() < 1e-15);
+    }
+
+    #[test]
+    fn quat_try_normalize_rejects_zero_and_non_finite() {
+        let q = Quat::new(0.0, 3.0, 0.0, 4.0);
+        assert_eq!(q.try_normalize(), Some(q.normalize()));
+        assert_eq!(Quat::new(0.0, 0.0, 0.0, 0.0).try_normalize(), None);
+        assert_eq!(Quat::new(1.0, f64::NAN, 0.0, 0.0).try_normalize(), None);
+        assert_eq!(
+            Quat::new(f64::INFINITY, 0.0, 0.0, 0.0).try_normalize(),
+            None
+        );
+        assert!(Quat::new(0.0, 0.0, 0.0, 0.0).normalize().w.is_nan());
+    }
+
+    #[test]
+    fn quat_accumulator_stays_on_unit_sphere() {
+        let mut acc = QuatAccumulator::new();
+        let omega = Vec3::new(3.0, -7.0, 11.0);
+        for _ in 0..1_000_000 {
+            acc.integrate(omega, 1.0 / 240.0);
+        }
+        let q = acc.resolve();
+        assert!(
+            (q.norm_squared_compensated() - 1.0).abs() <= QuatAccumulator::RENORMALIZE_TOLERANCE
+        );
+        // Rotation about ω leaves ω fixed.
+        assert!((q.rotate(omega) - omega).magnitude() < 1e-9);
+    }
+
+    #[test]
+    fn quat_accumulator_to_from_le_bytes_continues_bit_exactly() {
+        let omega = Vec3::new(0.3, -1.1, 2.0);
+        let mut acc = QuatAccumulator::new();
+        for _ in 0..1000 {
+            acc.integrate(omega, 1.0 / 60.0);
+        }
+        let mut restored = QuatAccumulator::from_le_bytes(acc.to_le_bytes());
+        for _ in 0..1000 {
+            acc.integrate(omega, 1.0 / 60.0);
+            restored.integrate(omega, 1.0 / 60.0);
+        }
+        assert_eq!(acc.to_le_bytes(), restored.to_le_bytes());
+    }
+
+    #[test]
+    fn quat_accumulator_small_step_matches_axis_angle() {
+        let omega = Vec3::new(0.0, 1.0, 0.0);
+        let dt = 1e-3;
+        let mut acc = QuatAccumulator::new();
+        for _ in 0..1000 {
+            acc.integrate(omega, dt);
+        }
+        let expected = Quat::from_axis_angle(omega, 1.0);
+        let q = acc.resolve();
+        assert!((q.dot(expected) - 1.0).abs() < 1e-12);
+    }
+}
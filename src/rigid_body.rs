@@ -0,0 +1,435 @@
+//! Rigid bodies with compensated position, orientation and momenta.
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+use crate::{eft, Mat3, Quat, QuatAccumulator, Vec3, Vec3Accumulator};
+
+/// A rigid body whose state is held in compensated accumulators
+///
+/// The state is position, linear momentum, orientation and world-frame
+/// angular momentum, with the mass and body-frame inertia tensor fixed at
+/// construction. Forces and torques applied between steps are summed with
+/// compensation too, so many small contributions are not lost.
+///
+/// [`step`](Self::step) is semi-implicit: the momenta are kicked by the
+/// applied forces first, then position and orientation move with the new
+/// momenta. Angular momentum is kept in the world frame, where it is
+/// constant without torque; the angular velocity `ω = R I⁻¹ Rᵀ L` is
+/// re-evaluated along the step, which is what produces precession and the
+/// other gyroscopic effects of Euler's equations.
+///
+/// # Example
+///
+/// ```rust
+/// use drift_linalg::{Mat3, Quat, RigidBody, Vec3};
+///
+/// // A 2 kg box, 1 × 2 × 3 m.
+/// let inertia = Mat3::from_diagonal(Vec3::new(13.0, 10.0, 5.0).scale(2.0 / 12.0));
+/// let mut body = RigidBody::new(Vec3::ZERO, Quat::IDENTITY, 2.0, inertia).unwrap();
+///
+/// for _ in 0..60 {
+///     // Gravity, plus a push on one corner for the first frame only.
+///     body.apply_force(Vec3::new(0.0, 0.0, -9.81 * 2.0));
+///     if body.position() == Vec3::ZERO {
+///         body.apply_force_at(Vec3::new(60.0, 0.0, 0.0), Vec3::new(0.5, 1.0, 1.5));
+///     }
+///     body.step(1.0 / 60.0);
+/// }
+///
+/// assert!(body.angular_momentum().magnitude() > 0.0);
+/// let bytes = body.to_le_bytes();
+/// assert_eq!(RigidBody::from_le_bytes(bytes).unwrap().to_le_bytes(), bytes);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct RigidBody {
+    position: Vec3Accumulator,
+    momentum: Vec3Accumulator,
+    orientation: QuatAccumulator,
+    angular_momentum: Vec3Accumulator,
+    force: Vec3Accumulator,
+    torque: Vec3Accumulator,
+    mass: f64,
+    inertia: Mat3,
+    inverse_inertia: Mat3,
+}
+
+impl RigidBody {
+    /// Fixed-point iterations used to solve for the midpoint angular
+    /// velocity. A fixed count keeps steps bit-reproducible.
+    const ROTATION_ITERATIONS: usize = 4;
+
+    /// Largest relative difference between `I[i][j]` and `I[j][i]` accepted
+    /// as symmetric, so tensors rotated into the body frame numerically
+    /// still pass.
+    const SYMMETRY_TOLERANCE: f64 = 1e-12;
+
+    /// Create a body at rest.
+    ///
+    /// `inertia` is the inertia tensor about the centre of mass in the body
+    /// frame. Returns `None` if `orientation` cannot be normalized (see
+    /// [`Quat::try_normalize`]), if `mass` is not positive and finite, or if
+    /// `inertia` is not finite, symmetric (to a relative 10⁻¹²) and positive
+    /// definite, i.e. does not have three positive principal moments.
+    pub fn new(position: Vec3, orientation: Quat, mass: f64, inertia: Mat3) -> Option<Self> {
+        orientation.try_normalize()?;
+        if !(mass > 0.0 && mass.is_finite() && Self::is_valid_inertia(&inertia)) {
+            return None;
+        }
+        Some(Self {
+            position: Vec3Accumulator::with_initial(position),
+            momentum: Vec3Accumulator::new(),
+            orientation: QuatAccumulator::with_initial(orientation),
+            angular_momentum: Vec3Accumulator::new(),
+            force: Vec3Accumulator::new(),
+            torque: Vec3Accumulator::new(),
+            mass,
+            inertia,
+            inverse_inertia: inertia.inverse_compensated()?,
+        })
+    }
+
+    /// The position of the centre of mass.
+    #[inline]
+    pub fn position(&self) -> Vec3 {
+        self.position.resolve()
+    }
+
+    /// The orientation, mapping body to world coordinates.
+    #[inline]
+    pub fn orientation(&self) -> Quat {
+        self.orientation.resolve()
+    }
+
+    /// The linear momentum `p = m v`.
+    #[inline]
+    pub fn momentum(&self) -> Vec3 {
+        self.momentum.resolve()
+    }
+
+    /// The angular momentum about the centre of mass, in the world frame.
+    #[inline]
+    pub fn angular_momentum(&self) -> Vec3 {
+        self.angular_momentum.resolve()
+    }
+
+    /// The mass.
+    #[inline]
+    pub fn mass(&self) -> f64 {
+        self.mass
+    }
+
+    /// The body-frame inertia tensor.
+    #[inline]
+    pub fn inertia(&self) -> Mat3 {
+        self.inertia
+    }
+
+    /// The velocity of the centre of mass.
+    #[inline]
+    pub fn velocity(&self) -> Vec3 {
+        self.momentum().scale(1.0 / self.mass)
+    }
+
+    /// The world-frame angular velocity `ω = R I⁻¹ Rᵀ L`.
+    #[inline]
+    pub fn angular_velocity(&self) -> Vec3 {
+        self.angular_velocity_at(self.orientation(), self.angular_momentum())
+    }
+
+    /// The velocity of the material point currently at world position
+    /// `point`.
+    #[inline]
+    pub fn velocity_at(&self, point: Vec3) -> Vec3 {
+        self.velocity() + self.angular_velocity().cross(point - self.position())
+    }
+
+    /// Translational plus rotational kinetic energy, `p²/2m + L·ω/2`.
+    #[inline]
+    pub fn kinetic_energy(&self) -> f64 {
+        let p = self.momentum();
+        let l = self.angular_momentum();
+        0.5 * (p.magnitude_squared_compensated() / self.mass
+            + l.dot_compensated(self.angular_velocity()))
+    }
+
+    /// Replace the linear momentum with `mass · velocity`.
+    #[inline]
+    pub fn set_velocity(&mut self, velocity: Vec3) {
+        self.momentum = Vec3Accumulator::with_initial(velocity.scale(self.mass));
+    }
+
+    /// Replace the angular momentum with the one giving world-frame angular
+    /// velocity `omega` at the current orientation.
+    #[inline]
+    pub fn set_angular_velocity(&mut self, omega: Vec3) {
+        let q = self.orientation();
+        let body = self
+            .inertia
+            .mul_vec3_compensated(q.conjugate().rotate(omega));
+        self.angular_momentum = Vec3Accumulator::with_initial(q.rotate(body));
+    }
+
+    /// Apply a force through the centre of mass until the next
+    /// [`step`](Self::step).
+    #[inline]
+    pub fn apply_force(&mut self, force: Vec3) {
+        self.force.add(force);
+    }
+
+    /// Apply a force at a world-space point until the next
+    /// [`step`](Self::step).
+    ///
+    /// This also applies the torque `(point - position) × force`.
+    #[inline]
+    pub fn apply_force_at(&mut self, force: Vec3, point: Vec3) {
+        self.force.add(force);
+        self.torque
+            .add((point - self.position()).cross_compensated(force));
+    }
+
+    /// Apply a world-frame torque until the next [`step`](Self::step).
+    #[inline]
+    pub fn apply_torque(&mut self, torque: Vec3) {
+        self.torque.add(torque);
+    }
+
+    /// Advance the body by `dt` and clear the applied forces and torques.
+    ///
+    /// The orientation moves by the Cayley rotation of `ω dt`, with `ω`
+    /// taken at the midpoint between the old and new orientations. The
+    /// midpoint is found by a fixed number of iterations, so the step is
+    /// symmetric in time up to the iteration error and bit-reproducible.
+    pub fn step(&mut self, dt: f64) {
+        self.momentum.add_scaled_exact(self.force.resolve(), dt);
+        self.angular_momentum
+            .add_scaled_exact(self.torque.resolve(), dt);
+        self.force.reset();
+        self.torque.reset();
+
+        self.position
+            .add_scaled_exact(self.momentum.resolve(), dt / self.mass);
+
+        let l = self.angular_momentum();
+        let q0 = self.orientation();
+        let mut omega = self.angular_velocity_at(q0, l);
+        for _ in 0..Self::ROTATION_ITERATIONS {
+            let mut next = self.orientation.clone();
+            next.integrate(omega, dt);
+            let q1 = next.resolve();
+            let midpoint = Quat::new(q0.w + q1.w, q0.x + q1.x, q0.y + q1.y, q0.z + q1.z);
+            omega = self.angular_velocity_at(midpoint.normalize(), l);
+        }
+        self.orientation.integrate(omega, dt);
+    }
+
+    /// Returns the full body state as little-endian bytes.
+    ///
+    /// The layout is the position, momentum, orientation, angular momentum,
+    /// pending force and pending torque accumulators including their
+    /// compensation (5 × 48 + 64 bytes), then the mass (8 bytes) and the
+    /// inertia tensor (72 bytes): 384 bytes in total. A body restored with
+    /// [`from_le_bytes`](Self::from_le_bytes) continues bit-exactly.
+    pub fn to_le_bytes(&self) -> [u8; 384] {
+        let mut buf = [0u8; 384];
+        buf[0..48].copy_from_slice(&self.position.to_le_bytes());
+        buf[48..96].copy_from_slice(&self.momentum.to_le_bytes());
+        buf[96..160].copy_from_slice(&self.orientation.to_le_bytes());
+        buf[160..208].copy_from_slice(&self.angular_momentum.to_le_bytes());
+        buf[208..256].copy_from_slice(&self.force.to_le_bytes());
+        buf[256..304].copy_from_slice(&self.torque.to_le_bytes());
+        buf[304..312].copy_from_slice(&self.mass.to_le_bytes());
+        buf[312..384].copy_from_slice(&self.inertia.to_le_bytes());
+        buf
+    }
+
+    /// Reconstruct a body from little-endian bytes.
+    ///
+    /// This is the inverse of [`to_le_bytes`](Self::to_le_bytes). Returns
+    /// `None` under the same conditions as [`new`](Self::new).
+    pub fn from_le_bytes(bytes: [u8; 384]) -> Option<Self> {
+        let accumulator =
+            |i: usize| Vec3Accumulator::from_le_bytes(bytes[i..i + 48].try_into().unwrap());
+        let mass = f64::from_le_bytes(bytes[304..312].try_into().unwrap());
+        let inertia = Mat3::from_le_bytes(bytes[312..384].try_into().unwrap());
+        let mut body = Self::new(Vec3::ZERO, Quat::IDENTITY, mass, inertia)?;
+        body.position = accumulator(0);
+        body.momentum = accumulator(48);
+        body.orientation = QuatAccumulator::from_le_bytes(bytes[96..160].try_into().unwrap());
+        body.angular_momentum = accumulator(160);
+        body.force = accumulator(208);
+        body.torque = accumulator(256);
+        Some(body)
+    }
+
+    /// Finite, symmetric and positive definite, the last checked with
+    /// Sylvester's criterion: all leading principal minors are positive.
+    fn is_valid_inertia(inertia: &Mat3) -> bool {
+        let m = &inertia.m;
+        if !m.iter().flatten().all(|x| x.is_finite()) {
+            return false;
+        }
+        let scale = m[0][0].abs().max(m[1][1].abs()).max(m[2][2].abs());
+        let symmetric = [(0, 1), (0, 2), (1, 2)]
+            .iter()
+            .all(|&(i, j)| (m[i][j] - m[j][i]).abs() <= Self::SYMMETRY_TOLERANCE * scale);
+        symmetric
+            && m[0][0] > 0.0
+            && eft::diff_of_products(m[0][0], m[1][1], m[0][1], m[1][0]) > 0.0
+            && inertia.determinant_compensated() > 0.0
+    }
+
+    /// `R I⁻¹ Rᵀ l` for orientation `q`.
+    #[inline]
+    fn angular_velocity_at(&self, q: Quat, l: Vec3) -> Vec3 {
+        let body = self
+            .inverse_inertia
+            .mul_vec3_compensated(q.conjugate().rotate(l));
+        q.rotate(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn box_inertia(mass: f64, size: Vec3) -> Mat3 {
+        let s = Vec3::new(size.x * size.x, size.y * size.y, size.z * size.z);
+        Mat3::from_diagonal(Vec3::new(s.y + s.z, s.x + s.z, s.x + s.y).scale(mass / 12.0))
+    }
+
+    #[test]
+    fn rejects_invalid_orientation_mass_and_inertia() {
+        let inertia = Mat3::IDENTITY;
+        let zero = Quat::new(0.0, 0.0, 0.0, 0.0);
+        let nan = Quat::new(f64::NAN, 0.0, 0.0, 1.0);
+        assert!(RigidBody::new(Vec3::ZERO, zero, 1.0, inertia).is_none());
+        assert!(RigidBody::new(Vec3::ZERO, nan, 1.0, inertia).is_none());
+        assert!(RigidBody::new(Vec3::ZERO, Quat::IDENTITY, 0.0, inertia).is_none());
+        assert!(RigidBody::new(Vec3::ZERO, Quat::IDENTITY, f64::NAN, inertia).is_none());
+        assert!(RigidBody::new(Vec3::ZERO, Quat::IDENTITY, 1.0, Mat3::ZERO).is_none());
+    }
+
+    #[test]
+    fn rejects_asymmetric_and_indefinite_inertia() {
+        let body = |m| RigidBody::new(Vec3::ZERO, Quat::IDENTITY, 1.0, Mat3::new(m));
+
+        // Symmetric with off-diagonal products of inertia: principal
+        // moments 2.5, 1.5 and 1.
+        let tilted = [[2.0, -0.5, 0.0], [-0.5, 2.0, 0.0], [0.0, 0.0, 1.0]];
+        assert!(body(tilted).is_some());
+        let mut rounded = tilted;
+        rounded[1][0] = f64::from_bits((-0.5f64).to_bits() + 1);
+        assert!(body(rounded).is_some());
+
+        let mut skewed = tilted;
+        skewed[1][0] = 0.5;
+        assert!(body(skewed).is_none());
+        // Invertible, but with a negative principal moment.
+        assert!(body([[1.0, 0.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, -1.0]]).is_none());
+        assert!(body([[-1.0, 0.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, 1.0]]).is_none());
+        assert!(body([[1.0, 2.0, 0.0], [2.0, 1.0, 0.0], [0.0, 0.0, 1.0]]).is_none());
+        assert!(body([[f64::INFINITY, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]).is_none());
+    }
+
+    #[test]
+    fn force_at_point_applies_torque() {
+        let mut body = RigidBody::new(
+            Vec3::new(1.0, 0.0, 0.0),
+            Quat::IDENTITY,
+            2.0,
+            Mat3::IDENTITY,
+        )
+        .unwrap();
+        body.apply_force_at(Vec3::new(0.0, 4.0, 0.0), Vec3::new(2.0, 0.0, 0.0));
+        body.step(0.5);
+        assert_eq!(body.momentum(), Vec3::new(0.0, 2.0, 0.0));
+        assert_eq!(body.angular_momentum(), Vec3::new(0.0, 0.0, 2.0));
+        assert_eq!(body.position(), Vec3::new(1.0, 0.5, 0.0));
+
+        // Forces are cleared after each step.
+        body.step(0.5);
+        assert_eq!(body.momentum(), Vec3::new(0.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn free_fall_is_exact_at_dyadic_steps() {
+        let mut body = RigidBody::new(Vec3::ZERO, Quat::IDENTITY, 3.0, Mat3::IDENTITY).unwrap();
+        for _ in 0..64 {
+            body.apply_force(Vec3::new(0.0, 0.0, -3.0));
+            body.step(0.125);
+        }
+        assert_eq!(body.velocity(), Vec3::new(0.0, 0.0, -8.0));
+        // Semi-implicit Euler: z = -h² n(n+1)/2.
+        assert_eq!(body.position().z, -0.125 * 0.125 * (64.0 * 65.0 / 2.0));
+    }
+
+    #[test]
+    fn torque_free_tumbling_conserves_energy_and_flips() {
+        // Spin mostly about the intermediate axis: the Dzhanibekov effect
+        // flips the body periodically, which only happens if the gyroscopic
+        // coupling is integrated.
+        let inertia = box_inertia(1.0, Vec3::new(1.0, 2.0, 3.0));
+        let mut body = RigidBody::new(Vec3::ZERO, Quat::IDENTITY, 1.0, inertia).unwrap();
+        body.set_angular_velocity(Vec3::new(1e-3, 5.0, 1e-3));
+        let l0 = body.angular_momentum();
+        let e0 = body.kinetic_energy();
+        let axis = |b: &RigidBody| b.orientation().rotate(Vec3::new(0.0, 1.0, 0.0)).y;
+
+        let mut worst: f64 = 0.0;
+        let mut flipped = false;
+        for _ in 0..20_000 {
+            body.step(1e-3);
+            worst = worst.max(((body.kinetic_energy() - e0) / e0).abs());
+            flipped |= axis(&body) < -0.9;
+        }
+        assert_eq!(body.angular_momentum(), l0);
+        assert!(worst < 1e-6, "{worst}");
+        assert!(flipped);
+    }
+
+    #[test]
+    fn spin_about_principal_axis_is_steady() {
+        let inertia = box_inertia(1.0, Vec3::new(1.0, 2.0, 3.0));
+        let mut body = RigidBody::new(Vec3::ZERO, Quat::IDENTITY, 1.0, inertia).unwrap();
+        let omega = Vec3::new(0.0, 0.0, 2.0);
+        body.set_angular_velocity(omega);
+        for _ in 0..10_000 {
+            body.step(1e-2);
+        }
+        assert!((body.angular_velocity() - omega).magnitude() < 1e-12);
+        let expected = Quat::from_axis_angle(omega, 200.0);
+        assert!((body.orientation().dot(expected).abs() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn to_from_le_bytes_continues_bit_exactly() {
+        let inertia = box_inertia(2.0, Vec3::new(1.0, 0.5, 0.25));
+        let mut body = RigidBody::new(
+            Vec3::new(1.0, 2.0, 3.0),
+            Quat::from_axis_angle(Vec3::new(1.0, 1.0, 0.0), 0.3),
+            2.0,
+            inertia,
+        )
+        .unwrap();
+        body.set_velocity(Vec3::new(0.1, 0.0, -0.2));
+        body.set_angular_velocity(Vec3::new(1.0, -2.0, 0.5));
+        let drive = |b: &mut RigidBody| {
+            for _ in 0..500 {
+                b.apply_force_at(
+                    Vec3::new(0.0, 0.0, -19.62),
+                    b.position() + Vec3::new(0.1, 0.0, 0.0),
+                );
+                b.step(1.0 / 120.0);
+            }
+        };
+        drive(&mut body);
+        body.apply_torque(Vec3::new(0.0, 0.3, 0.0));
+
+        let mut restored = RigidBody::from_le_bytes(body.to_le_bytes()).unwrap();
+        drive(&mut body);
+        drive(&mut restored);
+        assert_eq!(body.to_le_bytes(), restored.to_le_bytes());
+    }
+}
@@ -0,0 +1,89 @@
+//! Floating-point scalar types supported by the vectors and accumulators.
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+pub(crate) mod sealed {
+    /// Supertrait of [`Scalar`](super::Scalar) that downstream crates cannot
+    /// name, so they cannot implement it.
+    pub trait Sealed {}
+
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+}
+
+/// An IEEE-754 binary floating-point type: `f32` or `f64`
+///
+/// [`Vector3`](crate::Vector3), the compensated summation strategies and
+/// therefore [`Vec3Accumulator`](crate::Vec3Accumulator) are generic over
+/// this trait. The operations listed here are all correctly rounded by
+/// IEEE-754 (including [`mul_add`](Self::mul_add), which `std` implements in
+/// software on targets without hardware FMA), so generic code gives
+/// bit-identical results on every platform.
+///
+/// The trait is sealed: it is implemented for `f32` and `f64` only.
+pub trait Scalar:
+    sealed::Sealed
+    + Copy
+    + Default
+    + PartialEq
+    + PartialOrd
+    + fmt::Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+{
+    /// Zero.
+    const ZERO: Self;
+
+    /// Absolute value.
+    fn abs(self) -> Self;
+
+    /// Correctly rounded square root.
+    fn sqrt(self) -> Self;
+
+    /// Fused multiply-add, `self * a + b` with a single rounding.
+    fn mul_add(self, a: Self, b: Self) -> Self;
+}
+
+impl Scalar for f32 {
+    const ZERO: Self = 0.0;
+
+    #[inline]
+    fn abs(self) -> Self {
+        f32::abs(self)
+    }
+
+    #[inline]
+    fn sqrt(self) -> Self {
+        f32::sqrt(self)
+    }
+
+    #[inline]
+    fn mul_add(self, a: Self, b: Self) -> Self {
+        f32::mul_add(self, a, b)
+    }
+}
+
+impl Scalar for f64 {
+    const ZERO: Self = 0.0;
+
+    #[inline]
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+
+    #[inline]
+    fn sqrt(self) -> Self {
+        f64::sqrt(self)
+    }
+
+    #[inline]
+    fn mul_add(self, a: Self, b: Self) -> Self {
+        f64::mul_add(self, a, b)
+    }
+}
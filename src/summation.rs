@@ -0,0 +1,321 @@
+//! Compensated summation strategies.
+//!
+//! These are the scalar building blocks behind the vector accumulators.
+//! Every strategy implements [`Summation`], so accumulators such as
+//! [`Vec3Accumulator<S>`](crate::Vec3Accumulator) can be generic over the
+//! algorithm. They are also public so that downstream types implementing
+//! [`CompensatedAccumulate`](crate::CompensatedAccumulate) can compose them.
+//!
+//! | Strategy     | State   | Use when                                     |
+//! |--------------|---------|----------------------------------------------|
+//! | [`Kahan`]    | 2 × f64 | Terms never outgrow the running sum; cheapest |
+//! | [`Neumaier`] | 2 × f64 | General purpose; the default                 |
+//! | [`Klein`]    | 3 × f64 | Very long horizons; second-order correction  |
+//! | [`Exact`]    | 68 × i64 | Golden references; correctly rounded, order-independent |
+//! | [`Reproducible`] | 4 × i128 | Parallel reductions; order-independent, bounded error |
+//!
+//! [`Kahan`], [`Neumaier`] and [`Klein`] are generic over the [`Scalar`]
+//! type and default to f64; `Neumaier<f32>` gives compensated f32 sums.
+//! [`Exact`] and [`Reproducible`] sum f64 only.
+
+use std::fmt;
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+mod exact;
+mod reproducible;
+
+pub use exact::Exact;
+pub use reproducible::Reproducible;
+
+use crate::Scalar;
+
+/// A compensated scalar summation algorithm
+///
+/// All strategies expose the same API; pick one by type parameter.
+pub trait Summation: Copy + Default + PartialEq + fmt::Debug {
+    /// The floating-point type being summed.
+    type Scalar: Scalar;
+
+    /// Create a running sum starting at `initial`.
+    fn new(initial: Self::Scalar) -> Self;
+
+    /// Add a value, tracking the rounding error of the addition.
+    fn add(&mut self, value: Self::Scalar);
+
+    /// The compensated total.
+    fn total(&self) -> Self::Scalar;
+
+    /// Fold another running sum, including its compensation, into this one.
+    fn merge(&mut self, other: &Self);
+
+    /// Reset to zero.
+    #[inline]
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Classic Kahan compensated summation
+///
+/// The cheapest strategy. The compensation assumes each new term is not
+/// much larger than the running sum; when that fails (e.g. a large term
+/// cancels the sum, or when merging a partial sum of opposite sign), the
+/// lost low-order bits are not recovered. Prefer
+/// [`Neumaier`] unless the inputs are known to be well-behaved.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct Kahan<T: Scalar = f64> {
+    sum: T,
+    compensation: T,
+}
+
+impl<T: Scalar> Summation for Kahan<T> {
+    type Scalar = T;
+
+    #[inline]
+    fn new(initial: T) -> Self {
+        Self {
+            sum: initial,
+            compensation: T::ZERO,
+        }
+    }
+
+    #[inline]
+    fn add(&mut self, value: T) {
+        let y = value - self.compensation;
+        let t = self.sum + y;
+        self.compensation = (t - self.sum) - y;
+        self.sum = t;
+    }
+
+    /// The running sum with the pending correction applied.
+    #[inline]
+    fn total(&self) -> T {
+        self.sum - self.compensation
+    }
+
+    #[inline]
+    fn merge(&mut self, other: &Self) {
+        self.add(other.sum);
+        self.add(-other.compensation);
+    }
+}
+
+/// A Neumaier-compensated running sum with its state exposed
+///
+/// Each addition folds the rounding error of `sum + value` into a separate
+/// compensation term, choosing the error-free form by which operand is
+/// larger. The running sum and compensation term are kept as plain fields so
+/// that accumulators can be checkpointed and restored bit-exactly instead of
+/// being resolved first. This is the compensated sum behind every
+/// accumulator and compensated product in the crate.
+///
+/// The arithmetic is that of `drift_kernel::Neumaier`, copied here because
+/// drift-kernel keeps its state private. A test checks that both produce
+/// bit-identical totals.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct Neumaier<T: Scalar = f64> {
+    sum: T,
+    compensation: T,
+}
+
+impl<T: Scalar> Neumaier<T> {
+    /// The running sum, without the compensation applied.
+    #[inline]
+    pub fn sum(&self) -> T {
+        self.sum
+    }
+
+    /// The accumulated rounding error not yet folded into [`sum`](Self::sum).
+    #[inline]
+    pub fn compensation(&self) -> T {
+        self.compensation
+    }
+}
+
+impl Neumaier {
+    /// Returns the sum followed by the compensation as little-endian bytes.
+    #[inline]
+    pub fn to_le_bytes(self) -> [u8; 16] {
+        let mut buf = [0u8; 16];
+        buf[0..8].copy_from_slice(&self.sum.to_le_bytes());
+        buf[8..16].copy_from_slice(&self.compensation.to_le_bytes());
+        buf
+    }
+
+    /// Inverse of [`to_le_bytes`](Self::to_le_bytes).
+    #[inline]
+    pub fn from_le_bytes(bytes: [u8; 16]) -> Self {
+        Self {
+            sum: f64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+            compensation: f64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+        }
+    }
+}
+
+impl<T: Scalar> Summation for Neumaier<T> {
+    type Scalar = T;
+
+    #[inline]
+    fn new(initial: T) -> Self {
+        Self {
+            sum: initial,
+            compensation: T::ZERO,
+        }
+    }
+
+    #[inline]
+    fn add(&mut self, value: T) {
+        let t = self.sum + value;
+        if self.sum.abs() >= value.abs() {
+            self.compensation += (self.sum - t) + value;
+        } else {
+            self.compensation += (value - t) + self.sum;
+        }
+        self.sum = t;
+    }
+
+    #[inline]
+    fn total(&self) -> T {
+        self.sum + self.compensation
+    }
+
+    #[inline]
+    fn merge(&mut self, other: &Self) {
+        self.add(other.sum);
+        self.add(other.compensation);
+    }
+}
+
+/// Second-order Kahan–Babuška–Klein summation
+///
+/// Applies Neumaier's correction twice: once to the running sum and once
+/// to the first-order compensation itself. The extra term keeps the error
+/// independent of the number of additions even when the first-order
+/// compensation grows large, which is worth the cost on long-horizon runs.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct Klein<T: Scalar = f64> {
+    sum: T,
+    compensation: T,
+    second_order: T,
+}
+
+impl<T: Scalar> Summation for Klein<T> {
+    type Scalar = T;
+
+    #[inline]
+    fn new(initial: T) -> Self {
+        Self {
+            sum: initial,
+            compensation: T::ZERO,
+            second_order: T::ZERO,
+        }
+    }
+
+    #[inline]
+    fn add(&mut self, value: T) {
+        let t = self.sum + value;
+        let c = if self.sum.abs() >= value.abs() {
+            (self.sum - t) + value
+        } else {
+            (value - t) + self.sum
+        };
+        self.sum = t;
+
+        let t = self.compensation + c;
+        let cc = if self.compensation.abs() >= c.abs() {
+            (self.compensation - t) + c
+        } else {
+            (c - t) + self.compensation
+        };
+        self.compensation = t;
+        self.second_order += cc;
+    }
+
+    #[inline]
+    fn total(&self) -> T {
+        self.sum + (self.compensation + self.second_order)
+    }
+
+    #[inline]
+    fn merge(&mut self, other: &Self) {
+        self.add(other.sum);
+        self.add(other.compensation);
+        self.add(other.second_order);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sum_all<S: Summation<Scalar = f64>>(values: &[f64]) -> f64 {
+        let mut s = S::new(0.0);
+        values.iter().for_each(|&v| s.add(v));
+        s.total()
+    }
+
+    #[test]
+    fn summation_strategies_agree_on_well_conditioned_input() {
+        let values: Vec<f64> = (0..10_000).map(|i| 0.1 + i as f64 * 1e-3).collect();
+        let reference = sum_all::<Klein>(&values);
+        assert_eq!(sum_all::<Neumaier>(&values), reference);
+        assert!((sum_all::<Kahan>(&values) - reference).abs() <= reference * f64::EPSILON);
+    }
+
+    #[test]
+    fn summation_large_term_cancellation() {
+        // Kahan's compensation is lost when a term outgrows the running sum.
+        let values = [1.0, 1e100, 1.0, -1e100];
+        assert_eq!(sum_all::<Kahan>(&values), 0.0);
+        assert_eq!(sum_all::<Neumaier>(&values), 2.0);
+        assert_eq!(sum_all::<Klein>(&values), 2.0);
+    }
+
+    #[test]
+    fn summation_merge_keeps_compensation() {
+        // Kahan is excluded: merging a large opposite sum is exactly the
+        // case its compensation cannot recover.
+        fn check<S: Summation<Scalar = f64>>() {
+            let mut a = S::new(1e16);
+            a.add(1.0);
+            let mut b = S::new(-1e16);
+            b.add(1.0);
+            a.merge(&b);
+            assert_eq!(a.total(), 2.0, "{a:?}");
+            a.reset();
+            assert_eq!(a.total(), 0.0);
+        }
+        check::<Neumaier>();
+        check::<Klein>();
+    }
+
+    #[test]
+    fn neumaier_is_bit_identical_to_drift_kernel() {
+        // Signs and magnitudes spread over 2^±64, so both branches of the
+        // error term and heavy cancellation are exercised.
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let mantissa = (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+            mantissa * 2f64.powi((state % 129) as i32 - 64)
+        };
+        let mut ours = Neumaier::new(1.0);
+        let mut upstream = drift_kernel::Neumaier::new(1.0);
+        for i in 0..100_000 {
+            let value = next();
+            ours.add(value);
+            upstream.add(value);
+            assert_eq!(ours.total().to_bits(), upstream.total().to_bits(), "{i}");
+        }
+        ours.reset();
+        upstream.reset();
+        assert_eq!(ours.total().to_bits(), upstream.total().to_bits());
+    }
+}
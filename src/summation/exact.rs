@@ -0,0 +1,268 @@
+//! Exact summation with a fixed-point superaccumulator.
+
+use super::Summation;
+
+/// Number of 32-bit digits. Digit 0 starts at 2^-1088, so the digits cover
+/// every f64 bit (2^-1074 to 2^1023) with headroom above for carries.
+const LIMBS: usize = 68;
+const LIMB_BITS: u32 = 32;
+const LIMB_MASK: i64 = (1 << LIMB_BITS) - 1;
+
+/// Exponent of the least significant bit of limb 0.
+const BASE_EXPONENT: i32 = -1088;
+
+/// Bit position of 2^-1074, the smallest subnormal. No input has bits below it.
+const MIN_BIT: i32 = -1074 - BASE_EXPONENT;
+
+/// Additions between carry propagations. Each addition changes a limb by
+/// less than 2^32, so limbs stay well inside i64 range.
+const NORMALIZE_INTERVAL: u32 = 1 << 30;
+
+/// Exact summation with a correctly rounded result
+///
+/// Every f64 is added into a long fixed-point integer covering the whole
+/// exponent range, so no addition ever rounds. [`total`](Summation::total)
+/// rounds once, to nearest with ties to even, and the result is therefore
+/// independent of the order in which values were added or merged.
+///
+/// The state is 68 limbs (about 550 bytes) and each addition touches three
+/// of them, so this is meant for golden-reference runs and determinism
+/// audits rather than hot loops.
+///
+/// Non-finite inputs are summed separately in ordinary f64 arithmetic and,
+/// if any were added, returned as the total (`inf`, `-inf` or NaN). Zeros
+/// are ignored, so a sum of only negative zeros resolves to `+0.0`.
+#[derive(Debug, Clone, Copy)]
+pub struct Exact {
+    limbs: [i64; LIMBS],
+    special: f64,
+    pending: u32,
+}
+
+impl Exact {
+    #[inline]
+    fn normalize(&mut self) {
+        propagate_carries(&mut self.limbs);
+        self.pending = 0;
+    }
+
+    /// The value's limbs as a sign and a magnitude with every limb in
+    /// `[0, 2^32)` except the top one, which holds any overflow.
+    fn sign_magnitude(&self) -> (bool, [i64; LIMBS]) {
+        let mut m = self.limbs;
+        propagate_carries(&mut m);
+        let negative = m[LIMBS - 1] < 0;
+        if negative {
+            m.iter_mut().for_each(|l| *l = -*l);
+            propagate_carries(&mut m);
+        }
+        (negative, m)
+    }
+}
+
+impl Default for Exact {
+    fn default() -> Self {
+        Self {
+            limbs: [0; LIMBS],
+            special: 0.0,
+            pending: 0,
+        }
+    }
+}
+
+impl PartialEq for Exact {
+    /// Compares represented values, not the unnormalized limb layout.
+    fn eq(&self, other: &Self) -> bool {
+        self.sign_magnitude() == other.sign_magnitude() && self.special == other.special
+    }
+}
+
+impl Summation for Exact {
+    type Scalar = f64;
+
+    #[inline]
+    fn new(initial: f64) -> Self {
+        let mut s = Self::default();
+        s.add(initial);
+        s
+    }
+
+    fn add(&mut self, value: f64) {
+        if value == 0.0 {
+            return;
+        }
+        if !value.is_finite() {
+            self.special += value;
+            return;
+        }
+
+        let bits = value.to_bits();
+        let biased = ((bits >> 52) & 0x7ff) as i32;
+        let fraction = bits & ((1 << 52) - 1);
+        let (mantissa, exponent) = if biased == 0 {
+            (fraction, -1074)
+        } else {
+            (fraction | (1 << 52), biased - 1075)
+        };
+
+        let position = (exponent - BASE_EXPONENT) as u32;
+        let index = (position / LIMB_BITS) as usize;
+        let shifted = (mantissa as u128) << (position % LIMB_BITS);
+        for k in 0..3 {
+            let digit = ((shifted >> (LIMB_BITS * k as u32)) as i64) & LIMB_MASK;
+            if bits >> 63 == 1 {
+                self.limbs[index + k] -= digit;
+            } else {
+                self.limbs[index + k] += digit;
+            }
+        }
+
+        self.pending += 1;
+        if self.pending == NORMALIZE_INTERVAL {
+            self.normalize();
+        }
+    }
+
+    /// The exact sum, rounded once to nearest-even.
+    fn total(&self) -> f64 {
+        if self.special != 0.0 {
+            return self.special;
+        }
+        let (negative, m) = self.sign_magnitude();
+
+        let Some(top) = m.iter().rposition(|&l| l != 0) else {
+            return 0.0;
+        };
+        let len = top as i32 * LIMB_BITS as i32 + (64 - m[top].leading_zeros() as i32);
+
+        // Keep 53 significant bits, or fewer for subnormal results (which are
+        // always exact, since every input is a multiple of 2^-1074).
+        let mut cut = (len - 53).max(MIN_BIT);
+        let mut q = (cut..len)
+            .rev()
+            .fold(0u64, |q, pos| (q << 1) | bit(&m, pos));
+        if cut > MIN_BIT && bit(&m, cut - 1) == 1 && (q & 1 == 1 || any_below(&m, cut - 1)) {
+            q += 1;
+            if q == 1 << 53 {
+                q >>= 1;
+                cut += 1;
+            }
+        }
+
+        let magnitude = if q < 1 << 52 {
+            f64::from_bits(q)
+        } else {
+            let biased = cut + BASE_EXPONENT + 52 + 1023;
+            if biased >= 0x7ff {
+                f64::INFINITY
+            } else {
+                f64::from_bits(((biased as u64) << 52) | (q & ((1 << 52) - 1)))
+            }
+        };
+        if negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.normalize();
+        let mut theirs = other.limbs;
+        propagate_carries(&mut theirs);
+        for (mine, theirs) in self.limbs.iter_mut().zip(theirs) {
+            *mine += theirs;
+        }
+        self.normalize();
+        self.special += other.special;
+    }
+}
+
+/// Move each limb's overflow into the next one, leaving limbs below the top
+/// in `[0, 2^32)`. The top limb keeps the sign.
+#[inline]
+fn propagate_carries(limbs: &mut [i64; LIMBS]) {
+    for i in 0..LIMBS - 1 {
+        let carry = limbs[i] >> LIMB_BITS;
+        limbs[i] &= LIMB_MASK;
+        limbs[i + 1] += carry;
+    }
+}
+
+/// Bit `pos` of a non-negative normalized magnitude.
+#[inline]
+fn bit(m: &[i64; LIMBS], pos: i32) -> u64 {
+    let index = (pos as usize / LIMB_BITS as usize).min(LIMBS - 1);
+    let shift = pos as u32 - index as u32 * LIMB_BITS;
+    ((m[index] as u64) >> shift) & 1
+}
+
+/// Whether any bit strictly below `pos` is set.
+#[inline]
+fn any_below(m: &[i64; LIMBS], pos: i32) -> bool {
+    let index = pos as usize / LIMB_BITS as usize;
+    let partial = m[index] & ((1 << (pos as u32 % LIMB_BITS)) - 1);
+    partial != 0 || m[..index].iter().any(|&l| l != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exact_sum(values: &[f64]) -> f64 {
+        let mut s = Exact::default();
+        values.iter().for_each(|&v| s.add(v));
+        s.total()
+    }
+
+    #[test]
+    fn exact_rounds_once_to_nearest_even() {
+        let half_ulp = 2f64.powi(-53);
+        // A tie rounds to even...
+        assert_eq!(exact_sum(&[1.0, half_ulp]), 1.0);
+        // ...but any bit below the tie breaks it, even one far below.
+        assert_eq!(
+            exact_sum(&[1.0, half_ulp, 2f64.powi(-300)]),
+            1.0 + f64::EPSILON
+        );
+        assert_eq!(
+            exact_sum(&[-1.0, -half_ulp, -2f64.powi(-300)]),
+            -1.0 - f64::EPSILON
+        );
+    }
+
+    #[test]
+    fn exact_is_order_independent() {
+        let values: Vec<f64> = (0..2000)
+            .map(|i| {
+                let sign = if i % 3 == 0 { -1.0 } else { 1.0 };
+                sign * (1.0 + i as f64 * 0.37) * 2f64.powi((i * 37 % 200) - 100)
+            })
+            .collect();
+        let forward = exact_sum(&values);
+        let reversed: Vec<f64> = values.iter().rev().copied().collect();
+        assert_eq!(exact_sum(&reversed).to_bits(), forward.to_bits());
+
+        let (left, right) = values.split_at(777);
+        let mut a = Exact::default();
+        left.iter().for_each(|&v| a.add(v));
+        let mut b = Exact::default();
+        right.iter().for_each(|&v| b.add(v));
+        b.merge(&a);
+        assert_eq!(b.total().to_bits(), forward.to_bits());
+    }
+
+    #[test]
+    fn exact_handles_range_extremes() {
+        assert_eq!(exact_sum(&[f64::MAX, f64::MAX, -f64::MAX]), f64::MAX);
+        assert_eq!(exact_sum(&[f64::MAX, f64::MAX]), f64::INFINITY);
+        let tiny = f64::from_bits(1);
+        assert_eq!(exact_sum(&[tiny, tiny, tiny]), f64::from_bits(3));
+        assert_eq!(
+            exact_sum(&[f64::MIN_POSITIVE, -tiny]).to_bits(),
+            f64::MIN_POSITIVE.to_bits() - 1
+        );
+        assert_eq!(exact_sum(&[f64::INFINITY, 1.0]), f64::INFINITY);
+        assert!(exact_sum(&[f64::INFINITY, f64::NEG_INFINITY]).is_nan());
+    }
+}
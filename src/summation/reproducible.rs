@@ -0,0 +1,255 @@
+//! Order-independent binned summation.
+
+use super::{Exact, Summation};
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+/// Width of each bin in bits.
+const BIN_BITS: u32 = 32;
+const BIN_MASK: u128 = (1 << BIN_BITS) - 1;
+
+/// Number of bins kept below (and including) the bin of the largest input.
+const FOLDS: usize = 4;
+
+/// Reproducible summation over a fixed grid of exponent bins
+///
+/// In the style of ReproBLAS: the exponent range is cut into fixed 32-bit
+/// bins, and every input is pre-rounded by splitting its significand along
+/// the bin boundaries. Only the [`FOLDS`](Self::FOLDS) bins ending at the
+/// bin of the largest-magnitude input are kept; each holds the exact
+/// integer sum of its slices. Because the grid is fixed and the window
+/// depends only on the largest input, the state (and therefore
+/// [`total`](Summation::total)) is bit-identical for any permutation of the
+/// inputs and any merge tree.
+///
+/// At least 96 bits below the leading bit of the largest input are kept,
+/// so the absolute error is below `n · 2^-96 · max|x|` plus the final
+/// rounding. State is a few words, much smaller than [`Exact`].
+///
+/// Non-finite inputs are summed separately and returned as the total, as
+/// with [`Exact`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct Reproducible {
+    /// Grid index of `bins[0]`, or -1 when nothing finite has been added.
+    top: i32,
+    /// Exact per-bin sums; `bins[k]` holds bin `top - k` in units of its lowest bit.
+    bins: [i128; FOLDS],
+    special: f64,
+}
+
+impl Reproducible {
+    /// Number of bins kept, counting down from the bin of the largest input.
+    pub const FOLDS: usize = FOLDS;
+
+    /// Move the window up so its top bin is `top`, dropping bins that fall
+    /// out of the bottom.
+    #[inline]
+    fn raise(&mut self, top: i32) {
+        let shift = (top - self.top) as usize;
+        if self.top < 0 || shift >= FOLDS {
+            self.bins = [0; FOLDS];
+        } else {
+            self.bins.copy_within(0..FOLDS - shift, shift);
+            self.bins[..shift].fill(0);
+        }
+        self.top = top;
+    }
+}
+
+impl Default for Reproducible {
+    fn default() -> Self {
+        Self {
+            top: -1,
+            bins: [0; FOLDS],
+            special: 0.0,
+        }
+    }
+}
+
+impl Summation for Reproducible {
+    type Scalar = f64;
+
+    #[inline]
+    fn new(initial: f64) -> Self {
+        let mut s = Self::default();
+        s.add(initial);
+        s
+    }
+
+    fn add(&mut self, value: f64) {
+        if value == 0.0 {
+            return;
+        }
+        if !value.is_finite() {
+            self.special += value;
+            return;
+        }
+
+        // value = ±mantissa · 2^(position - 1074), position >= 0
+        let bits = value.to_bits();
+        let biased = (bits >> 52) & 0x7ff;
+        let fraction = bits & ((1 << 52) - 1);
+        let (mantissa, position) = if biased == 0 {
+            (fraction, 0)
+        } else {
+            (fraction | (1 << 52), biased as u32 - 1)
+        };
+
+        let leading = position + (63 - mantissa.leading_zeros());
+        let leading_bin = (leading / BIN_BITS) as i32;
+        if leading_bin > self.top {
+            self.raise(leading_bin);
+        }
+
+        let base_bin = (position / BIN_BITS) as i32;
+        let shifted = (mantissa as u128) << (position % BIN_BITS);
+        for k in 0..3 {
+            let slot = self.top - (base_bin + k);
+            if slot < 0 || slot as usize >= FOLDS {
+                continue;
+            }
+            let digit = ((shifted >> (BIN_BITS * k as u32)) & BIN_MASK) as i128;
+            if bits >> 63 == 1 {
+                self.bins[slot as usize] -= digit;
+            } else {
+                self.bins[slot as usize] += digit;
+            }
+        }
+    }
+
+    /// The windowed sum, rounded once to nearest-even.
+    fn total(&self) -> f64 {
+        if self.special != 0.0 {
+            return self.special;
+        }
+        // Propagate carries upward so every bin but the top one lies in
+        // [0, 2^32). Only the top bin can then exceed the f64 range, and a
+        // top bin that does means the sum itself overflows.
+        let mut bins = self.bins;
+        for k in (1..FOLDS).rev() {
+            let carry = bins[k] >> BIN_BITS;
+            bins[k] -= carry << BIN_BITS;
+            bins[k - 1] += carry;
+        }
+        // Each bin is re-expressed as exact 32-bit f64 pieces and rounded once.
+        let mut exact = Exact::default();
+        for (k, &bin) in bins.iter().enumerate() {
+            let mut magnitude = bin.unsigned_abs();
+            let mut exponent = (self.top - k as i32) * BIN_BITS as i32 - 1074;
+            while magnitude != 0 {
+                let piece = scale((magnitude & BIN_MASK) as f64, exponent);
+                exact.add(if bin < 0 { -piece } else { piece });
+                magnitude >>= BIN_BITS;
+                exponent += BIN_BITS as i32;
+            }
+        }
+        exact.total()
+    }
+
+    fn merge(&mut self, other: &Self) {
+        if other.top > self.top {
+            self.raise(other.top);
+        }
+        for (k, &bin) in other.bins.iter().enumerate() {
+            let slot = self.top - other.top + k as i32;
+            if (slot as usize) < FOLDS {
+                self.bins[slot as usize] += bin;
+            }
+        }
+        self.special += other.special;
+    }
+}
+
+/// `value · 2^exponent` for `exponent` in `[-2148, 2046]`.
+///
+/// Split into two power-of-two factors, each finite, so the product is exact
+/// whenever the result is representable, infinite when it overflows, and
+/// never `0 · inf`.
+#[inline]
+fn scale(value: f64, exponent: i32) -> f64 {
+    let half = exponent / 2;
+    value * pow2(half) * pow2(exponent - half)
+}
+
+/// `2^exponent` for `exponent` in `[-1074, 1023]`.
+#[inline]
+fn pow2(exponent: i32) -> f64 {
+    debug_assert!((-1074..=1023).contains(&exponent));
+    if exponent >= -1022 {
+        f64::from_bits(((exponent + 1023) as u64) << 52)
+    } else {
+        f64::from_bits(1 << (exponent + 1074))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reproducible_sum(values: &[f64]) -> Reproducible {
+        let mut s = Reproducible::default();
+        values.iter().for_each(|&v| s.add(v));
+        s
+    }
+
+    fn wide_range_values() -> Vec<f64> {
+        (0..5000)
+            .map(|i| {
+                let sign = if i % 5 < 2 { -1.0 } else { 1.0 };
+                sign * (1.0 + (i * 7919 % 1000) as f64 / 997.0) * 2f64.powi((i * 31 % 120) - 60)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reproducible_permutation_invariant() {
+        let values = wide_range_values();
+        let forward = reproducible_sum(&values);
+
+        let mut shuffled = values.clone();
+        // Deterministic Fisher-Yates with an LCG, so the test is repeatable.
+        let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+        for i in (1..shuffled.len()).rev() {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            shuffled.swap(i, (seed >> 33) as usize % (i + 1));
+        }
+        let permuted = reproducible_sum(&shuffled);
+        assert_eq!(permuted, forward);
+        assert_eq!(permuted.total().to_bits(), forward.total().to_bits());
+
+        let mut merged = reproducible_sum(&shuffled[2500..]);
+        merged.merge(&reproducible_sum(&shuffled[..2500]));
+        assert_eq!(merged.total().to_bits(), forward.total().to_bits());
+    }
+
+    #[test]
+    fn reproducible_matches_exact_within_window() {
+        // All inputs within 96 bits of the largest: nothing is dropped.
+        let values = [1.0, 2f64.powi(-60), -3.0, 0.1, 2f64.powi(-90)];
+        let mut exact = Exact::default();
+        values.iter().for_each(|&v| exact.add(v));
+        assert_eq!(reproducible_sum(&values).total(), exact.total());
+
+        // Inputs far below the window are dropped deterministically.
+        assert_eq!(reproducible_sum(&[1e30, 1e-30, -1e30]).total(), 0.0);
+        assert_eq!(reproducible_sum(&[1e-30, 1e30, -1e30]).total(), 0.0);
+    }
+
+    #[test]
+    fn reproducible_handles_sums_near_overflow() {
+        let max = f64::MAX;
+        assert_eq!(reproducible_sum(&[max, max, -max]).total(), max);
+        assert_eq!(reproducible_sum(&[max, -max, max, 1.0]).total(), max);
+        assert_eq!(reproducible_sum(&[max, max]).total(), f64::INFINITY);
+        assert_eq!(reproducible_sum(&[-max, -max]).total(), f64::NEG_INFINITY);
+        let (half, small) = (max / 2.0, 2f64.powi(960));
+        assert_eq!(
+            reproducible_sum(&[max, half, -half, -max, small]).total(),
+            small
+        );
+    }
+}
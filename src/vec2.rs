@@ -0,0 +1,147 @@
+//! 2D vectors and their drift-free accumulator.
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+use crate::summation::{Neumaier, Summation};
+use crate::{eft, macros, Scalar};
+
+/// A 2D vector generic over its [`Scalar`] component type
+///
+/// Most code uses the [`Vec2`] (f64) or [`Vec2F32`] aliases. For
+/// accumulation across many operations, use [`Vec2Accumulator`] instead.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct Vector2<T: Scalar = f64> {
+    pub x: T,
+    pub y: T,
+}
+
+/// A standard 2D vector with f64 components
+pub type Vec2 = Vector2<f64>;
+
+/// A 2D vector with f32 components
+pub type Vec2F32 = Vector2<f32>;
+
+impl<T: Scalar> Vector2<T> {
+    /// Compute the perpendicular dot product `x₁y₂ - y₁x₂`, the 2D analogue
+    /// of the cross product.
+    #[inline]
+    pub fn perp_dot(&self, other: Self) -> T {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// Compute the perpendicular dot product with Kahan's
+    /// difference-of-products algorithm (at most 1.5 ulp error).
+    #[inline]
+    pub fn perp_dot_compensated(&self, other: Self) -> T {
+        eft::diff_of_products(self.x, other.y, self.y, other.x)
+    }
+}
+
+macros::vector_impls! {
+    Vector2, Vec2, Vec2F32, Vec2Accumulator,
+    { x: 0, y: 1 },
+    len: 2,
+    bytes: (16, 8)
+}
+
+/// A 2D spatial accumulator
+///
+/// The 2D counterpart of [`Vec3Accumulator`](crate::Vec3Accumulator): each
+/// component is a compensated running sum (Neumaier by default), and the
+/// summation strategy is a type parameter.
+///
+/// # Example
+///
+/// ```rust
+/// use drift_linalg::{Vec2, Vec2Accumulator};
+///
+/// let mut acc = Vec2Accumulator::new();
+/// for _ in 0..100_000 {
+///     acc.add(Vec2::new(1e15, 1.0));
+///     acc.add(Vec2::new(-1e15, -1.0));
+/// }
+/// assert_eq!(acc.resolve(), Vec2::ZERO);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct Vec2Accumulator<S: Summation = Neumaier> {
+    x: S,
+    y: S,
+}
+
+macros::accumulator_impls! {
+    Vec2Accumulator, Vector2, Vec2,
+    { x, y },
+    bytes: 32
+}
+
+/// A [`Vec2Accumulator`] over f32 components
+pub type Vec2AccumulatorF32 = Vec2Accumulator<Neumaier<f32>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vec2_to_from_le_bytes_roundtrip() {
+        let original = Vec2::new(1.5, -2.25);
+        let bytes = original.to_le_bytes();
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(Vec2::from_le_bytes(bytes), original);
+        let small = Vec2F32::new(0.1, -3.0);
+        assert_eq!(Vec2F32::from_le_bytes(small.to_le_bytes()), small);
+    }
+
+    #[test]
+    fn vec2_products() {
+        let a = Vec2::new(3.0, 4.0);
+        assert_eq!(a.magnitude(), 5.0);
+        assert_eq!(a.dot(Vec2::new(-4.0, 3.0)), 0.0);
+        assert_eq!(a.perp_dot(Vec2::new(1.0, 0.0)), -4.0);
+        assert_eq!(a.scale(2.0) - a, a);
+        assert_eq!(2.0 * a, a + a);
+        assert_eq!(a[1], 4.0);
+        assert_eq!(Vec2::from([3.0, 4.0]), a);
+
+        let e = 2f64.powi(-30);
+        let b = Vec2::new(1.0 + e, 1.0);
+        let c = Vec2::new(1.0 + e, -(1.0 + 2.0 * e));
+        assert_eq!(b.dot(c), 0.0);
+        assert_eq!(b.dot_compensated(c), e * e);
+        assert_eq!(
+            b.perp_dot_compensated(Vec2::new(1.0 + 2.0 * e, 1.0 + e)),
+            e * e
+        );
+    }
+
+    #[test]
+    fn vec2_accumulator_roundtrip_and_merge() {
+        let mut acc = Vec2Accumulator::with_initial(Vec2::new(1e16, -1e16));
+        acc.add_scaled_exact(Vec2::new(0.1, 0.2), 3.0);
+        let restored = Vec2Accumulator::from_le_bytes(acc.to_le_bytes());
+        assert_eq!(restored.to_le_bytes(), acc.to_le_bytes());
+
+        let mut other = Vec2Accumulator::with_initial(Vec2::new(-1e16, 1e16));
+        other.add(Vec2::new(1.0, 1.0));
+        acc.merge(&other);
+        let merged = acc.resolve();
+        assert_eq!(merged, Vec2::new(1.0 + 0.1 * 3.0, 1.0 + 0.2 * 3.0));
+
+        let parts = [acc.clone(), Vec2Accumulator::new(), other];
+        let reduced = Vec2Accumulator::tree_reduce(&parts).resolve();
+        assert_eq!(reduced, Vec2::new(-1e16 + 2.3, 1e16 + 2.6));
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn vec2_accumulator_serde_preserves_compensation() {
+        let mut acc = Vec2Accumulator::with_initial(Vec2::new(1e16, -1e16));
+        acc.add(Vec2::new(1.0, 1.0));
+        let json = serde_json::to_string(&acc).unwrap();
+        let mut restored: Vec2Accumulator = serde_json::from_str(&json).unwrap();
+        restored.add(Vec2::new(-1e16, 1e16));
+        assert_eq!(restored.resolve(), Vec2::new(1.0, 1.0));
+    }
+}
@@ -0,0 +1,132 @@
+//! 4D vectors and their drift-free accumulator.
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+use crate::summation::{Neumaier, Summation};
+use crate::{macros, Scalar, Vector3};
+
+/// A 4D vector generic over its [`Scalar`] component type
+///
+/// Most code uses the [`Vec4`] (f64) or [`Vec4F32`] aliases, e.g. for
+/// homogeneous coordinates. For accumulation across many operations, use
+/// [`Vec4Accumulator`] instead.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct Vector4<T: Scalar = f64> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    pub w: T,
+}
+
+/// A standard 4D vector with f64 components
+pub type Vec4 = Vector4<f64>;
+
+/// A 4D vector with f32 components
+pub type Vec4F32 = Vector4<f32>;
+
+impl<T: Scalar> Vector4<T> {
+    /// Extend a 3D vector with a `w` component.
+    #[inline]
+    pub fn from_vec3(v: Vector3<T>, w: T) -> Self {
+        Self::new(v.x, v.y, v.z, w)
+    }
+
+    /// The `x, y, z` components.
+    #[inline]
+    pub fn xyz(&self) -> Vector3<T> {
+        Vector3::new(self.x, self.y, self.z)
+    }
+}
+
+macros::vector_impls! {
+    Vector4, Vec4, Vec4F32, Vec4Accumulator,
+    { x: 0, y: 1, z: 2, w: 3 },
+    len: 4,
+    bytes: (32, 16)
+}
+
+/// A 4D accumulator
+///
+/// The 4D counterpart of [`Vec3Accumulator`](crate::Vec3Accumulator): each
+/// component is a compensated running sum (Neumaier by default), and the
+/// summation strategy is a type parameter.
+///
+/// # Example
+///
+/// ```rust
+/// use drift_linalg::{Vec4, Vec4Accumulator};
+///
+/// let mut acc = Vec4Accumulator::new();
+/// for _ in 0..100_000 {
+///     acc.add(Vec4::new(1e15, 1e-15, 1.0, 0.5));
+///     acc.add(Vec4::new(-1e15, -1e-15, -1.0, -0.5));
+/// }
+/// assert_eq!(acc.resolve(), Vec4::ZERO);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct Vec4Accumulator<S: Summation = Neumaier> {
+    x: S,
+    y: S,
+    z: S,
+    w: S,
+}
+
+macros::accumulator_impls! {
+    Vec4Accumulator, Vector4, Vec4,
+    { x, y, z, w },
+    bytes: 64
+}
+
+/// A [`Vec4Accumulator`] over f32 components
+pub type Vec4AccumulatorF32 = Vec4Accumulator<Neumaier<f32>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Vec3;
+
+    #[test]
+    fn vec4_to_from_le_bytes_roundtrip() {
+        let original = Vec4::new(1.5, -2.25, 3.125, 1.0);
+        let bytes = original.to_le_bytes();
+        assert_eq!(&bytes[24..32], &1.0f64.to_le_bytes());
+        assert_eq!(Vec4::from_le_bytes(bytes), original);
+        let small = Vec4F32::new(0.1, -3.0, 7.5, 0.0);
+        assert_eq!(Vec4F32::from_le_bytes(small.to_le_bytes()), small);
+    }
+
+    #[test]
+    fn vec4_products() {
+        let p = Vec4::from_vec3(Vec3::new(1.0, 2.0, 2.0), 4.0);
+        assert_eq!(p.magnitude(), 5.0);
+        assert_eq!(p.xyz(), Vec3::new(1.0, 2.0, 2.0));
+        assert_eq!(-p + p, Vec4::ZERO);
+
+        let e = 2f64.powi(-30);
+        let a = Vec4::new(1.0 + e, 1.0, 0.0, 0.0);
+        let b = Vec4::new(1.0 + e, -(1.0 + 2.0 * e), 0.0, 0.0);
+        assert_eq!(a.dot(b), 0.0);
+        assert_eq!(a.dot_compensated(b), e * e);
+    }
+
+    #[test]
+    fn vec4_accumulator_roundtrip_and_merge() {
+        let mut acc = Vec4Accumulator::with_initial(Vec4::new(1e16, -1e16, 1e16, 0.0));
+        acc.add_scaled(Vec4::new(1.0, 1.0, 1.0, 1.0), 0.5);
+        let restored = Vec4Accumulator::from_le_bytes(acc.to_le_bytes());
+        assert_eq!(restored.to_le_bytes(), acc.to_le_bytes());
+
+        let mut other = Vec4Accumulator::with_initial(Vec4::new(-1e16, 1e16, -1e16, 0.0));
+        other.add(Vec4::new(1.0, 1.0, 1.0, 1.0));
+        acc.merge(&other);
+        assert_eq!(acc.resolve(), Vec4::new(1.5, 1.5, 1.5, 1.5));
+
+        acc.reset();
+        assert_eq!(acc.resolve(), Vec4::ZERO);
+        let empty: &[Vec4Accumulator] = &[];
+        assert_eq!(Vec4Accumulator::tree_reduce(empty).resolve(), Vec4::ZERO);
+    }
+}
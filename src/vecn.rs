@@ -0,0 +1,419 @@
+//! Fixed-size N-dimensional vectors and their drift-free accumulator.
+
+use crate::eft;
+use crate::summation::{Neumaier, Summation};
+
+/// An N-dimensional f64 vector stored inline
+///
+/// For state vectors such as the generalized coordinates of an articulated
+/// body. The components live in a plain array, so values are `Copy` and
+/// never touch the heap. For accumulation, use [`AccumulatorN`].
+///
+/// # Example
+///
+/// ```rust
+/// use drift_linalg::VecN;
+///
+/// let q = VecN::new([1.0, 2.0, 2.0, 0.0, 0.0, 0.0]);
+/// assert_eq!(q.magnitude(), 3.0);
+/// let bytes: [u8; 48] = q.to_le_bytes();
+/// assert_eq!(VecN::from_le_bytes(bytes), q);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VecN<const N: usize>(pub [f64; N]);
+
+impl<const N: usize> VecN<N> {
+    /// The zero vector.
+    pub const ZERO: Self = Self([0.0; N]);
+
+    /// Create a new VecN.
+    #[inline]
+    pub const fn new(components: [f64; N]) -> Self {
+        Self(components)
+    }
+
+    /// Returns the raw IEEE-754 little-endian bytes of each component in
+    /// order (8 × N bytes).
+    ///
+    /// Stable Rust cannot name `[u8; 8 * N]`, so the length is a second
+    /// parameter, usually inferred from the binding. A length other than
+    /// `8 * N` is a compile error, but it is raised when the call is
+    /// monomorphized: `cargo build` reports it, `cargo check` does not.
+    #[inline]
+    pub fn to_le_bytes<const B: usize>(&self) -> [u8; B] {
+        const { assert!(B == 8 * N, "byte length must be 8 * N") };
+        let mut buf = [0u8; B];
+        for (chunk, v) in buf.chunks_exact_mut(8).zip(&self.0) {
+            chunk.copy_from_slice(&v.to_le_bytes());
+        }
+        buf
+    }
+
+    /// Reconstruct a VecN from little-endian bytes.
+    ///
+    /// This is the inverse of [`to_le_bytes`](Self::to_le_bytes).
+    #[inline]
+    pub fn from_le_bytes<const B: usize>(bytes: [u8; B]) -> Self {
+        const { assert!(B == 8 * N, "byte length must be 8 * N") };
+        let mut components = [0.0; N];
+        for (v, chunk) in components.iter_mut().zip(bytes.chunks_exact(8)) {
+            *v = f64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        Self(components)
+    }
+
+    /// Compute the dot product with another vector.
+    #[inline]
+    pub fn dot(&self, other: &Self) -> f64 {
+        self.0.iter().zip(&other.0).map(|(a, b)| a * b).sum()
+    }
+
+    /// Compute the dot product with compensated arithmetic.
+    ///
+    /// See [`Vector3::dot_compensated`](crate::Vector3::dot_compensated); the
+    /// result is as accurate as if computed in twice the working precision
+    /// for any `N`.
+    #[inline]
+    pub fn dot_compensated(&self, other: &Self) -> f64 {
+        eft::dot2(&self.0, &other.0)
+    }
+
+    /// Compute the squared magnitude (avoids sqrt).
+    #[inline]
+    pub fn magnitude_squared(&self) -> f64 {
+        self.dot(self)
+    }
+
+    /// Compute the squared magnitude with compensated arithmetic.
+    #[inline]
+    pub fn magnitude_squared_compensated(&self) -> f64 {
+        self.dot_compensated(self)
+    }
+
+    /// Compute the magnitude.
+    #[inline]
+    pub fn magnitude(&self) -> f64 {
+        self.magnitude_squared().sqrt()
+    }
+
+    /// Compute the magnitude from the compensated squared magnitude.
+    #[inline]
+    pub fn magnitude_compensated(&self) -> f64 {
+        self.magnitude_squared_compensated().sqrt()
+    }
+
+    /// Scale by a scalar.
+    #[inline]
+    pub fn scale(&self, scalar: f64) -> Self {
+        Self(self.0.map(|v| v * scalar))
+    }
+}
+
+impl<const N: usize> Default for VecN<N> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<const N: usize> From<[f64; N]> for VecN<N> {
+    fn from(components: [f64; N]) -> Self {
+        Self(components)
+    }
+}
+
+impl<const N: usize> std::ops::Index<usize> for VecN<N> {
+    type Output = f64;
+    fn index(&self, index: usize) -> &f64 {
+        &self.0[index]
+    }
+}
+
+impl<const N: usize> std::ops::IndexMut<usize> for VecN<N> {
+    fn index_mut(&mut self, index: usize) -> &mut f64 {
+        &mut self.0[index]
+    }
+}
+
+impl<const N: usize> std::ops::Add for VecN<N> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self(std::array::from_fn(|i| self.0[i] + rhs.0[i]))
+    }
+}
+
+impl<const N: usize> std::ops::Sub for VecN<N> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self(std::array::from_fn(|i| self.0[i] - rhs.0[i]))
+    }
+}
+
+impl<const N: usize> std::ops::Neg for VecN<N> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self(self.0.map(|v| -v))
+    }
+}
+
+/// An N-dimensional drift-free accumulator
+///
+/// The same per-component compensation as
+/// [`Vec3Accumulator`](crate::Vec3Accumulator), for [`VecN`] state vectors.
+/// The state is an inline array of `N` running sums, so it is `Clone`,
+/// stack-allocated and cheap to keep per body in a hot loop.
+///
+/// # Example
+///
+/// ```rust
+/// use drift_linalg::{AccumulatorN, VecN};
+///
+/// let mut q = AccumulatorN::<6>::new();
+/// let qdot = VecN::new([1e15, 1e-15, 1.0, -1.0, 0.5, 0.0]);
+/// for _ in 0..100_000 {
+///     q.add(qdot);
+///     q.add(-qdot);
+/// }
+/// assert_eq!(q.resolve(), VecN::ZERO);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AccumulatorN<const N: usize, S: Summation<Scalar = f64> = Neumaier> {
+    components: [S; N],
+}
+
+impl<const N: usize> AccumulatorN<N> {
+    /// Create a new zero-initialized accumulator.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an accumulator with an initial value.
+    #[inline]
+    pub fn with_initial(initial: VecN<N>) -> Self {
+        Self::from_initial(initial)
+    }
+
+    /// Returns the full accumulator state as little-endian bytes.
+    ///
+    /// The layout is the running sum followed by the compensation term for
+    /// each component (16 × N bytes). As with [`VecN::to_le_bytes`], a wrong
+    /// length is rejected when the call is monomorphized by `cargo build`.
+    #[inline]
+    pub fn to_le_bytes<const B: usize>(&self) -> [u8; B] {
+        const { assert!(B == 16 * N, "byte length must be 16 * N") };
+        let mut buf = [0u8; B];
+        for (chunk, s) in buf.chunks_exact_mut(16).zip(&self.components) {
+            chunk.copy_from_slice(&s.to_le_bytes());
+        }
+        buf
+    }
+
+    /// Reconstruct an accumulator from little-endian bytes.
+    ///
+    /// This is the inverse of [`to_le_bytes`](Self::to_le_bytes).
+    #[inline]
+    pub fn from_le_bytes<const B: usize>(bytes: [u8; B]) -> Self {
+        const { assert!(B == 16 * N, "byte length must be 16 * N") };
+        let mut acc = Self::new();
+        for (s, chunk) in acc.components.iter_mut().zip(bytes.chunks_exact(16)) {
+            *s = Neumaier::from_le_bytes(chunk.try_into().unwrap());
+        }
+        acc
+    }
+}
+
+impl<const N: usize, S: Summation<Scalar = f64>> AccumulatorN<N, S> {
+    /// Create an accumulator with an initial value using strategy `S`.
+    #[inline]
+    pub fn from_initial(initial: VecN<N>) -> Self {
+        Self {
+            components: initial.0.map(S::new),
+        }
+    }
+
+    /// Add a vector to the accumulator.
+    #[inline]
+    pub fn add(&mut self, vec: VecN<N>) {
+        for (s, v) in self.components.iter_mut().zip(vec.0) {
+            s.add(v);
+        }
+    }
+
+    /// Add a scaled vector to the accumulator.
+    ///
+    /// The scalar multiplication is not compensated; see
+    /// [`Vec3Accumulator::add_scaled`](crate::Vec3Accumulator::add_scaled).
+    #[inline]
+    pub fn add_scaled(&mut self, vec: VecN<N>, scalar: f64) {
+        for (s, v) in self.components.iter_mut().zip(vec.0) {
+            s.add(v * scalar);
+        }
+    }
+
+    /// Add a scaled vector to the accumulator with an error-free product.
+    #[inline]
+    pub fn add_scaled_exact(&mut self, vec: VecN<N>, scalar: f64) {
+        for (s, v) in self.components.iter_mut().zip(vec.0) {
+            let (p, e) = eft::two_product(v, scalar);
+            s.add(p);
+            s.add(e);
+        }
+    }
+
+    /// Fold another accumulator into this one without resolving either.
+    #[inline]
+    pub fn merge(&mut self, other: &Self) {
+        for (s, o) in self.components.iter_mut().zip(&other.components) {
+            s.merge(o);
+        }
+    }
+
+    /// Merge a slice of partial accumulators in a fixed pairwise tree order.
+    ///
+    /// See [`Vec3Accumulator::tree_reduce`](crate::Vec3Accumulator::tree_reduce).
+    pub fn tree_reduce(parts: &[Self]) -> Self {
+        match parts {
+            [] => Self::default(),
+            [single] => single.clone(),
+            _ => {
+                let (left, right) = parts.split_at(parts.len() / 2);
+                let mut acc = Self::tree_reduce(left);
+                acc.merge(&Self::tree_reduce(right));
+                acc
+            }
+        }
+    }
+
+    /// Resolve the accumulator to a standard VecN.
+    #[inline]
+    pub fn resolve(&self) -> VecN<N> {
+        VecN(self.components.map(|s| s.total()))
+    }
+
+    /// Reset the accumulator to zero.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.components.iter_mut().for_each(S::reset);
+    }
+}
+
+impl<const N: usize, S: Summation<Scalar = f64>> Default for AccumulatorN<N, S> {
+    fn default() -> Self {
+        Self::from_initial(VecN::ZERO)
+    }
+}
+
+/// Serde support. Arrays longer than 32 have no derived impls, so both
+/// types serialize as a sequence of `N` elements.
+#[cfg(feature = "serialization")]
+mod serde_impls {
+    use super::{AccumulatorN, VecN};
+    use crate::summation::Summation;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    fn to_array<T, E: Error, const N: usize>(items: Vec<T>) -> Result<[T; N], E> {
+        let len = items.len();
+        items
+            .try_into()
+            .map_err(|_| E::invalid_length(len, &format!("{N} elements").as_str()))
+    }
+
+    impl<const N: usize> Serialize for VecN<N> {
+        fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+            self.0.as_slice().serialize(serializer)
+        }
+    }
+
+    impl<'de, const N: usize> Deserialize<'de> for VecN<N> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            to_array(Vec::deserialize(deserializer)?).map(VecN)
+        }
+    }
+
+    impl<const N: usize, S> Serialize for AccumulatorN<N, S>
+    where
+        S: Summation<Scalar = f64> + Serialize,
+    {
+        fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+            self.components.as_slice().serialize(serializer)
+        }
+    }
+
+    impl<'de, const N: usize, S> Deserialize<'de> for AccumulatorN<N, S>
+    where
+        S: Summation<Scalar = f64> + Deserialize<'de>,
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let components = to_array(Vec::deserialize(deserializer)?)?;
+            Ok(AccumulatorN { components })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vecn_matches_vec3_and_roundtrips() {
+        let a = VecN::new([1.5, -2.25, 3.125]);
+        let v = crate::Vec3::new(1.5, -2.25, 3.125);
+        let bytes: [u8; 24] = a.to_le_bytes();
+        assert_eq!(bytes, v.to_le_bytes());
+        assert_eq!(VecN::from_le_bytes(bytes), a);
+        assert_eq!(a.dot(&a), v.dot(v));
+        assert_eq!((a + a - a.scale(2.0))[1], 0.0);
+    }
+
+    #[test]
+    fn vecn_dot_compensated_long_vector() {
+        // Large terms cancel pairwise; only the small products survive.
+        let mut a = [0.0; 30];
+        let mut b = [0.0; 30];
+        for i in 0..10 {
+            a[3 * i] = 1e16;
+            a[3 * i + 1] = -1e16;
+            a[3 * i + 2] = 1.0;
+            b[3 * i] = 1.0;
+            b[3 * i + 1] = 1.0;
+            b[3 * i + 2] = 0.5;
+        }
+        let (a, b) = (VecN::new(a), VecN::new(b));
+        assert_eq!(a.dot_compensated(&b), 5.0);
+        assert_eq!(VecN::new([3.0, 4.0]).magnitude_compensated(), 5.0);
+    }
+
+    #[test]
+    fn accumulator_n_keeps_compensation() {
+        let mut acc = AccumulatorN::with_initial(VecN::new([1e16; 8]));
+        acc.add(VecN::new([1.0; 8]));
+        let bytes: [u8; 128] = acc.to_le_bytes();
+        let mut restored = AccumulatorN::<8>::from_le_bytes(bytes);
+
+        let mut other = AccumulatorN::with_initial(VecN::new([-1e16; 8]));
+        other.add_scaled_exact(VecN::new([0.1; 8]), 10.0);
+        restored.merge(&other);
+        assert_eq!(restored.resolve(), VecN::new([2.0; 8]));
+
+        let parts = [acc.clone(), other.clone(), AccumulatorN::new()];
+        assert_eq!(
+            AccumulatorN::tree_reduce(&parts).resolve(),
+            restored.resolve()
+        );
+        restored.reset();
+        assert_eq!(restored.resolve(), VecN::ZERO);
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn accumulator_n_serde_preserves_compensation() {
+        let mut acc = AccumulatorN::with_initial(VecN::new([1e16; 40]));
+        acc.add(VecN::new([1.0; 40]));
+        let json = serde_json::to_string(&acc).unwrap();
+        let mut restored: AccumulatorN<40> = serde_json::from_str(&json).unwrap();
+        restored.add(VecN::new([-1e16; 40]));
+        assert_eq!(restored.resolve(), VecN::new([1.0; 40]));
+        assert!(serde_json::from_str::<AccumulatorN<39>>(&json).is_err());
+    }
+}
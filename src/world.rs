@@ -0,0 +1,390 @@
+//! Large-world coordinates: integer sectors plus a local f64 offset.
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+use crate::{eft, Vec3, Vec3Accumulator};
+
+/// Edge length of a sector in world units.
+///
+/// A power of two, so that dividing by it and multiplying sector counts by
+/// it are exact, and normalization is bit-reproducible.
+pub const SECTOR_SIZE: f64 = 1024.0;
+
+/// A position in a world larger than f64 can resolve
+///
+/// The integer `sector` selects a cube of edge [`SECTOR_SIZE`] and
+/// `offset` is the position within it, normalized to `[0, SECTOR_SIZE)` on
+/// each axis. Precision is therefore uniform everywhere: about 2^-43 units
+/// anywhere in the world.
+///
+/// The world spans 2^64 sectors per axis and wraps around: sector
+/// arithmetic is wrapping `i64` arithmetic, so moving past `i64::MAX`
+/// continues at `i64::MIN`, and [`difference`](Self::difference) returns
+/// the shortest separation around the wrap. Use
+/// [`checked_new`](Self::checked_new) or
+/// [`checked_from_vec3`](Self::checked_from_vec3) to reject positions that
+/// would wrap instead.
+///
+/// Normalization is deterministic rather than exact. Dividing by
+/// [`SECTOR_SIZE`], `floor` and the product `whole · SECTOR_SIZE` are exact,
+/// but the remainder `v - whole · SECTOR_SIZE` is rounded when `v` is
+/// negative and much smaller than a sector: `-1e-20` becomes `1024 - 1e-20`,
+/// which rounds to the sector edge and is carried into the next sector as
+/// offset 0. Every step is a single correctly rounded IEEE-754 operation,
+/// so the result is identical on every platform.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct WorldPosition {
+    pub sector: [i64; 3],
+    pub offset: Vec3,
+}
+
+impl WorldPosition {
+    /// The world origin.
+    pub const ORIGIN: Self = Self {
+        sector: [0; 3],
+        offset: Vec3::ZERO,
+    };
+
+    /// Create a position from a sector and an offset, normalizing the
+    /// offset into `[0, SECTOR_SIZE)`.
+    #[inline]
+    pub fn new(sector: [i64; 3], offset: Vec3) -> Self {
+        let mut p = Self { sector, offset };
+        p.normalize();
+        p
+    }
+
+    /// Create a position from a sector and an offset, or `None` if the
+    /// offset is not finite or normalizing it would wrap a sector.
+    #[inline]
+    pub fn checked_new(sector: [i64; 3], offset: Vec3) -> Option<Self> {
+        let axis = |s: i64, v: f64| {
+            if !v.is_finite() {
+                return None;
+            }
+            let (whole, rest) = split_axis(v);
+            if !(-I64_RANGE..I64_RANGE).contains(&whole) {
+                return None;
+            }
+            Some((s.checked_add(whole as i64)?, rest))
+        };
+        let (sx, x) = axis(sector[0], offset.x)?;
+        let (sy, y) = axis(sector[1], offset.y)?;
+        let (sz, z) = axis(sector[2], offset.z)?;
+        Some(Self {
+            sector: [sx, sy, sz],
+            offset: Vec3::new(x, y, z),
+        })
+    }
+
+    /// Create a position from absolute world coordinates.
+    ///
+    /// Coordinates beyond about ±9.4e21 units (2^63 sectors) wrap around;
+    /// see [`checked_from_vec3`](Self::checked_from_vec3).
+    #[inline]
+    pub fn from_vec3(position: Vec3) -> Self {
+        Self::new([0; 3], position)
+    }
+
+    /// Create a position from absolute world coordinates, or `None` if they
+    /// are not finite or lie outside the world.
+    #[inline]
+    pub fn checked_from_vec3(position: Vec3) -> Option<Self> {
+        Self::checked_new([0; 3], position)
+    }
+
+    /// Move whole sectors out of the offset until it lies in `[0, SECTOR_SIZE)`.
+    ///
+    /// Sectors wrap on overflow.
+    #[inline]
+    pub fn normalize(&mut self) {
+        let o = self.offset;
+        let (sx, x) = split_axis(o.x);
+        let (sy, y) = split_axis(o.y);
+        let (sz, z) = split_axis(o.z);
+        for (s, whole) in self.sector.iter_mut().zip([sx, sy, sz]) {
+            *s = s.wrapping_add(wrap_to_i64(whole));
+        }
+        self.offset = Vec3::new(x, y, z);
+    }
+
+    /// Return this position moved by `delta`.
+    #[inline]
+    pub fn translate(&self, delta: Vec3) -> Self {
+        Self::new(self.sector, self.offset + delta)
+    }
+
+    /// The vector from `origin` to `self`.
+    ///
+    /// The sector difference is taken with wrapping, so it is the shortest
+    /// separation around the world. The offsets are combined with
+    /// error-free sums so the result is accurate to about one rounding.
+    #[inline]
+    pub fn difference(&self, origin: &WorldPosition) -> Vec3 {
+        let axis = |i: usize, a: f64, b: f64| {
+            let sectors = self.sector[i].wrapping_sub(origin.sector[i]) as f64 * SECTOR_SIZE;
+            let (d, e1) = eft::two_sum(a, -b);
+            let (s, e2) = eft::two_sum(sectors, d);
+            s + (e1 + e2)
+        };
+        let (a, b) = (self.offset, origin.offset);
+        Vec3::new(axis(0, a.x, b.x), axis(1, a.y, b.y), axis(2, a.z, b.z))
+    }
+
+    /// Returns the raw little-endian bytes: three i64 sectors, then the
+    /// offset as in [`Vec3::to_le_bytes`] (48 bytes).
+    #[inline]
+    pub fn to_le_bytes(&self) -> [u8; 48] {
+        let mut buf = [0u8; 48];
+        for (i, s) in self.sector.iter().enumerate() {
+            buf[i * 8..i * 8 + 8].copy_from_slice(&s.to_le_bytes());
+        }
+        buf[24..48].copy_from_slice(&self.offset.to_le_bytes());
+        buf
+    }
+
+    /// Reconstruct a WorldPosition from little-endian bytes.
+    ///
+    /// This is the inverse of [`to_le_bytes`](Self::to_le_bytes).
+    #[inline]
+    pub fn from_le_bytes(bytes: [u8; 48]) -> Self {
+        let sector = |i: usize| i64::from_le_bytes(bytes[i * 8..i * 8 + 8].try_into().unwrap());
+        Self {
+            sector: [sector(0), sector(1), sector(2)],
+            offset: Vec3::from_le_bytes(bytes[24..48].try_into().unwrap()),
+        }
+    }
+}
+
+/// Shift every position so that `new_origin` becomes sector `[0, 0, 0]`.
+///
+/// Only the integer sectors change, with wrapping, so offsets keep their
+/// exact bits and the rebase can be undone bit-exactly with the negated
+/// origin.
+pub fn rebase(positions: &mut [WorldPosition], new_origin: [i64; 3]) {
+    for p in positions {
+        for (s, o) in p.sector.iter_mut().zip(new_origin) {
+            *s = s.wrapping_sub(o);
+        }
+    }
+}
+
+/// A drift-free accumulator over a [`WorldPosition`]
+///
+/// The offset is a [`Vec3Accumulator`]; whenever its resolved value leaves
+/// `[0, SECTOR_SIZE)`, whole sectors are subtracted from it (as a
+/// compensated add, so nothing is lost) and added to the integer sector.
+/// The offset therefore never grows large enough to lose precision.
+///
+/// # Example
+///
+/// ```rust
+/// use drift_linalg::{Vec3, WorldPosition, WorldPositionAccumulator};
+///
+/// let mut ship = WorldPositionAccumulator::new(WorldPosition::ORIGIN);
+/// let velocity = Vec3::new(3.0e8, 0.0, 0.0);
+///
+/// for _ in 0..60 * 60 {
+///     ship.add_scaled_exact(velocity, 1.0 / 60.0);
+/// }
+///
+/// // One light-minute, still resolved to sub-millimeter precision.
+/// let p = ship.resolve();
+/// let expected = WorldPosition::from_vec3(Vec3::new(1.8e10, 0.0, 0.0));
+/// assert!(p.difference(&expected).magnitude() < 1e-3);
+/// assert!(p.offset.x < drift_linalg::SECTOR_SIZE);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct WorldPositionAccumulator {
+    sector: [i64; 3],
+    offset: Vec3Accumulator,
+}
+
+impl WorldPositionAccumulator {
+    /// Create an accumulator starting at `initial`.
+    #[inline]
+    pub fn new(initial: WorldPosition) -> Self {
+        Self {
+            sector: initial.sector,
+            offset: Vec3Accumulator::with_initial(initial.offset),
+        }
+    }
+
+    /// Add a displacement.
+    #[inline]
+    pub fn add(&mut self, delta: Vec3) {
+        self.offset.add(delta);
+        self.roll_over();
+    }
+
+    /// Add a scaled displacement. See [`Vec3Accumulator::add_scaled`].
+    #[inline]
+    pub fn add_scaled(&mut self, vec: Vec3, scalar: f64) {
+        self.offset.add_scaled(vec, scalar);
+        self.roll_over();
+    }
+
+    /// Add a scaled displacement with an error-free product.
+    /// See [`Vec3Accumulator::add_scaled_exact`].
+    #[inline]
+    pub fn add_scaled_exact(&mut self, vec: Vec3, scalar: f64) {
+        self.offset.add_scaled_exact(vec, scalar);
+        self.roll_over();
+    }
+
+    /// Resolve to a normalized WorldPosition.
+    #[inline]
+    pub fn resolve(&self) -> WorldPosition {
+        WorldPosition::new(self.sector, self.offset.resolve())
+    }
+
+    #[inline]
+    fn roll_over(&mut self) {
+        let o = self.offset.resolve();
+        let whole = [o.x, o.y, o.z].map(|v| (v / SECTOR_SIZE).floor());
+        if whole != [0.0; 3] {
+            self.offset
+                .add(Vec3::new(whole[0], whole[1], whole[2]).scale(-SECTOR_SIZE));
+            for (s, w) in self.sector.iter_mut().zip(whole) {
+                *s = s.wrapping_add(wrap_to_i64(w));
+            }
+        }
+    }
+}
+
+/// 2^63, the magnitude bound of `i64`.
+const I64_RANGE: f64 = 9_223_372_036_854_775_808.0;
+
+/// Split one offset axis into a whole number of sectors and a remainder in
+/// `[0, SECTOR_SIZE)`.
+#[inline]
+fn split_axis(v: f64) -> (f64, f64) {
+    let whole = (v / SECTOR_SIZE).floor();
+    let rest = v - whole * SECTOR_SIZE;
+    // Exact except for small negative `v`, where it rounds and can reach
+    // exactly SECTOR_SIZE.
+    if rest >= SECTOR_SIZE {
+        (whole + 1.0, 0.0)
+    } else {
+        (whole, rest)
+    }
+}
+
+/// An integer-valued f64 reduced modulo 2^64 into `i64`, so that sector
+/// counts wrap instead of saturating. Non-finite values map to 0.
+#[inline]
+fn wrap_to_i64(whole: f64) -> i64 {
+    // `%` is exact, and the correction by 2^64 is exact because the operand
+    // is at least 2^63 in magnitude.
+    let r = whole % (2.0 * I64_RANGE);
+    let r = if r >= I64_RANGE {
+        r - 2.0 * I64_RANGE
+    } else if r < -I64_RANGE {
+        r + 2.0 * I64_RANGE
+    } else {
+        r
+    };
+    r as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn world_position_normalizes_offset() {
+        let p = WorldPosition::new([0, 0, 0], Vec3::new(2500.5, -1.0, 1024.0));
+        assert_eq!(p.sector, [2, -1, 1]);
+        assert_eq!(p.offset, Vec3::new(452.5, 1023.0, 0.0));
+
+        // -1e-300 + 1024 rounds to 1024, which must roll into the next sector.
+        let q = WorldPosition::new([5, 5, 5], Vec3::new(-1e-300, 0.0, 0.0));
+        assert_eq!(q.sector, [5, 5, 5]);
+        assert_eq!(q.offset.x, 0.0);
+    }
+
+    #[test]
+    fn world_position_difference_far_from_origin() {
+        let a = WorldPosition::new([1 << 50, -(1 << 50), 7], Vec3::new(0.001, 0.002, 0.003));
+        let b = WorldPosition::new([1 << 50, -(1 << 50), 6], Vec3::new(0.0, 0.0, 1023.999));
+        let d = a.difference(&b);
+        assert_eq!(d.x, 0.001);
+        assert_eq!(d.y, 0.002);
+        assert!((d.z - 0.004).abs() < 1e-12);
+    }
+
+    #[test]
+    fn world_position_rebase_is_bit_exact() {
+        let mut positions = vec![
+            WorldPosition::new([10, 20, 30], Vec3::new(0.1, 0.2, 0.3)),
+            WorldPosition::new([-4, 0, 9], Vec3::new(1e-9, 512.0, 1023.5)),
+        ];
+        let original = positions.clone();
+        rebase(&mut positions, [10, 20, 30]);
+        assert_eq!(positions[0].sector, [0, 0, 0]);
+        assert_eq!(positions[1].offset, original[1].offset);
+        rebase(&mut positions, [-10, -20, -30]);
+        assert_eq!(positions, original);
+        let bytes = original[1].to_le_bytes();
+        assert_eq!(WorldPosition::from_le_bytes(bytes), original[1]);
+    }
+
+    #[test]
+    fn world_position_sectors_wrap() {
+        let p = WorldPosition::new([i64::MAX - 1, 0, 0], Vec3::new(5000.0, 0.0, 0.0));
+        assert_eq!(p.sector[0], i64::MIN + 2);
+        assert_eq!(p.offset.x, 5000.0 - 4096.0);
+        let q = WorldPosition::new([i64::MAX - 1, 0, 0], Vec3::ZERO);
+        assert_eq!(p.difference(&q), Vec3::new(5000.0, 0.0, 0.0));
+
+        // 2^64 sectors is a full turn.
+        let turn = WorldPosition::from_vec3(Vec3::new(2f64.powi(74), -2f64.powi(74), 0.0));
+        assert_eq!(turn, WorldPosition::ORIGIN);
+
+        let mut positions = [p];
+        rebase(&mut positions, [i64::MIN, 0, 0]);
+        assert_eq!(positions[0].sector[0], 2);
+        assert_eq!(positions[0].offset, p.offset);
+    }
+
+    #[test]
+    fn world_position_checked_rejects_wrapping() {
+        assert_eq!(
+            WorldPosition::checked_from_vec3(Vec3::new(1e25, 0.0, 0.0)),
+            None
+        );
+        assert_eq!(
+            WorldPosition::checked_from_vec3(Vec3::new(f64::NAN, 0.0, 0.0)),
+            None
+        );
+        assert_eq!(
+            WorldPosition::checked_new([i64::MAX - 1, 0, 0], Vec3::new(5000.0, 0.0, 0.0)),
+            None
+        );
+        let p = WorldPosition::checked_new([i64::MIN + 5, 0, 0], Vec3::new(-5000.0, 1.5, 2048.0));
+        assert_eq!(
+            p,
+            Some(WorldPosition::new(
+                [i64::MIN + 5, 0, 0],
+                Vec3::new(-5000.0, 1.5, 2048.0)
+            ))
+        );
+        assert_eq!(p.unwrap().sector, [i64::MIN, 0, 2]);
+    }
+
+    #[test]
+    fn world_position_accumulator_rolls_into_sectors() {
+        let mut acc = WorldPositionAccumulator::new(WorldPosition::ORIGIN);
+        for _ in 0..1_000_000 {
+            acc.add_scaled_exact(Vec3::new(10.0, -0.1, 0.001), 1.0 / 60.0);
+        }
+        let p = acc.resolve();
+        let expected = WorldPosition::from_vec3(Vec3::new(1e7, -1e5, 1e3).scale(1.0 / 60.0));
+        let d = p.difference(&expected);
+        assert!(d.magnitude() < 1e-9, "{d:?}");
+        assert!(p.offset.x >= 0.0 && p.offset.x < SECTOR_SIZE);
+    }
+}